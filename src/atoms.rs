@@ -0,0 +1,45 @@
+xcb::atoms_struct! {
+    pub struct Atoms {
+        pub xrwm_tag => b"_XRWM_TAG",
+        pub xrwm_time => b"_XRWM_TIME",
+        pub xrwm_layout_name => b"_XRWM_LAYOUT_NAME",
+        pub xrwm_layout_names => b"_XRWM_LAYOUT_NAMES",
+        pub utf8_string => b"UTF8_STRING",
+        pub compound_text => b"COMPOUND_TEXT",
+        pub net_wm_name => b"_NET_WM_NAME",
+        pub net_wm_pid => b"_NET_WM_PID",
+        pub net_supported => b"_NET_SUPPORTED",
+        pub net_supporting_wm_check => b"_NET_SUPPORTING_WM_CHECK",
+        pub net_client_list => b"_NET_CLIENT_LIST",
+        pub net_client_list_stacking => b"_NET_CLIENT_LIST_STACKING",
+        pub net_active_window => b"_NET_ACTIVE_WINDOW",
+        pub net_number_of_desktops => b"_NET_NUMBER_OF_DESKTOPS",
+        pub net_current_desktop => b"_NET_CURRENT_DESKTOP",
+        pub net_desktop_names => b"_NET_DESKTOP_NAMES",
+        pub net_wm_desktop => b"_NET_WM_DESKTOP",
+        pub net_close_window => b"_NET_CLOSE_WINDOW",
+        pub net_wm_state => b"_NET_WM_STATE",
+        pub net_wm_state_fullscreen => b"_NET_WM_STATE_FULLSCREEN",
+        pub net_wm_state_above => b"_NET_WM_STATE_ABOVE",
+        pub net_wm_state_below => b"_NET_WM_STATE_BELOW",
+        pub net_wm_state_sticky => b"_NET_WM_STATE_STICKY",
+        pub net_wm_state_hidden => b"_NET_WM_STATE_HIDDEN",
+        pub net_wm_state_demands_attention => b"_NET_WM_STATE_DEMANDS_ATTENTION",
+        pub net_wm_window_type => b"_NET_WM_WINDOW_TYPE",
+        pub net_wm_window_type_desktop => b"_NET_WM_WINDOW_TYPE_DESKTOP",
+        pub net_wm_window_type_dock => b"_NET_WM_WINDOW_TYPE_DOCK",
+        pub net_wm_window_type_toolbar => b"_NET_WM_WINDOW_TYPE_TOOLBAR",
+        pub net_wm_window_type_menu => b"_NET_WM_WINDOW_TYPE_MENU",
+        pub net_wm_window_type_utility => b"_NET_WM_WINDOW_TYPE_UTILITY",
+        pub net_wm_window_type_splash => b"_NET_WM_WINDOW_TYPE_SPLASH",
+        pub net_wm_window_type_dialog => b"_NET_WM_WINDOW_TYPE_DIALOG",
+        pub net_wm_window_type_notification => b"_NET_WM_WINDOW_TYPE_NOTIFICATION",
+        pub net_wm_window_type_normal => b"_NET_WM_WINDOW_TYPE_NORMAL",
+        pub net_wm_strut => b"_NET_WM_STRUT",
+        pub net_wm_strut_partial => b"_NET_WM_STRUT_PARTIAL",
+        pub net_workarea => b"_NET_WORKAREA",
+        pub wm_protocols => b"WM_PROTOCOLS",
+        pub wm_delete_window => b"WM_DELETE_WINDOW",
+        pub wm_take_focus => b"WM_TAKE_FOCUS",
+    }
+}
@@ -1,28 +1,229 @@
 use std::{
-    fs::{create_dir, File},
+    collections::HashMap,
+    fs::{create_dir_all, File},
     io::Read,
+    str::FromStr,
 };
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use toml::de::Error;
 
 #[derive(Clone, Deserialize)]
+#[serde(default)]
 pub struct Config {
+    /// Tag names, tags are numbered from 1 in this order.
+    tags: Vec<String>,
     tiling_config: TilingConfig,
+    window_config: WindowConfig,
+    focus_config: FocusConfig,
+    /// Layout of the tags that aren't in `tag_layouts`.
+    layout: LayoutKind,
+    /// Layouts of single tags by name, like `"3" = "monocle"`.
+    tag_layouts: HashMap<String, LayoutKind>,
+    monitor_tags: MonitorTags,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            tags: (1..=9).map(|tag| tag.to_string()).collect(),
+            tiling_config: TilingConfig::default(),
+            window_config: WindowConfig::default(),
+            focus_config: FocusConfig::default(),
+            layout: LayoutKind::Tile,
+            tag_layouts: HashMap::new(),
+            monitor_tags: MonitorTags::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LayoutKind {
+    Tile,
+    Monocle,
+    Grid,
+    Spiral,
+    Dwindle,
+    CenteredMaster,
+    Bsp,
+    Containers,
+    Scrolling,
+    Floating,
+}
+
+impl LayoutKind {
+    /// Order of `cycle-layout`.
+    pub const ALL: [LayoutKind; 10] = [
+        LayoutKind::Tile,
+        LayoutKind::Monocle,
+        LayoutKind::Grid,
+        LayoutKind::Spiral,
+        LayoutKind::Dwindle,
+        LayoutKind::CenteredMaster,
+        LayoutKind::Bsp,
+        LayoutKind::Containers,
+        LayoutKind::Scrolling,
+        LayoutKind::Floating,
+    ];
+}
+
+impl FromStr for LayoutKind {
+    type Err = String;
+
+    fn from_str(layout: &str) -> Result<Self, Self::Err> {
+        Ok(match layout {
+            "tile" => LayoutKind::Tile,
+            "monocle" => LayoutKind::Monocle,
+            "grid" => LayoutKind::Grid,
+            "spiral" => LayoutKind::Spiral,
+            "dwindle" => LayoutKind::Dwindle,
+            "centered-master" => LayoutKind::CenteredMaster,
+            "bsp" => LayoutKind::Bsp,
+            "containers" => LayoutKind::Containers,
+            "scrolling" => LayoutKind::Scrolling,
+            "floating" => LayoutKind::Floating,
+            _ => return Err(format!("unknown layout `{}`", layout)),
+        })
+    }
 }
 
 #[derive(Clone, Deserialize)]
+#[serde(default)]
 pub struct TilingConfig {
     gaps_config: Option<GapsConfig>,
+    /// Windows in the master area.
+    master_count: usize,
+    /// Part of the area taken by the master windows.
+    master_ratio: f32,
+    master_position: MasterPosition,
+    /// No borders when a single window is shown.
+    smart_borders: bool,
+    /// No gaps when a single window is shown.
+    smart_gaps: bool,
+    /// Part of the area width taken by a new column of the scrolling layout.
+    column_width: f32,
+}
+
+impl Default for TilingConfig {
+    fn default() -> Self {
+        TilingConfig {
+            gaps_config: None,
+            master_count: 1,
+            master_ratio: 0.55,
+            master_position: MasterPosition::Left,
+            smart_borders: false,
+            smart_gaps: false,
+            column_width: 0.5,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MasterPosition {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl FromStr for MasterPosition {
+    type Err = String;
+
+    fn from_str(position: &str) -> Result<Self, Self::Err> {
+        Ok(match position {
+            "left" => MasterPosition::Left,
+            "right" => MasterPosition::Right,
+            "top" => MasterPosition::Top,
+            "bottom" => MasterPosition::Bottom,
+            _ => return Err(format!("unknown master position `{}`", position)),
+        })
+    }
 }
 
 #[derive(Clone, Deserialize)]
+#[serde(default)]
 pub struct WindowConfig {
     border_size: u16,
-    border_radius: u16,
     open_in_center_on_floating_mode: bool,
+    /// Milliseconds a closed window gets before it's sent SIGTERM and then
+    /// killed. Windows aren't forced to close if it's not set.
+    close_timeout: Option<u64>,
+    /// Held to move windows with the left button and resize them with the
+    /// right one.
+    mouse_modifier: MouseModifier,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MouseModifier {
+    Super,
+    Alt,
+    Control,
+    Shift,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            border_size: 1,
+            open_in_center_on_floating_mode: true,
+            close_timeout: None,
+            mouse_modifier: MouseModifier::Super,
+        }
+    }
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FocusConfig {
+    focus_mode: FocusMode,
+    focus_stealing: FocusStealing,
+}
+
+/// How the monitors share the tags.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MonitorTags {
+    /// One pool of tags, every monitor shows one of them. Viewing a tag shown
+    /// on another monitor swaps the tags of the monitors.
+    #[default]
+    Shared,
+    /// Every monitor has its own tags.
+    PerMonitor,
+}
+
+/// Clicking a window always focuses it, the modes differ in how the
+/// pointer moves the focus.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FocusMode {
+    /// Only clicks focus.
+    #[default]
+    Click,
+    /// Entering a window focuses it, the focus stays when the pointer leaves
+    /// to the root window.
+    Sloppy,
+    /// Like `Sloppy`, but leaving to the root window unfocuses.
+    StrictFollowsMouse,
+}
+
+/// What happens when a client takes the focus or asks to be activated by
+/// itself.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FocusStealing {
+    Allow,
+    Deny,
+    /// The focus stays, the window is marked as demanding attention.
+    #[default]
+    Attention,
+}
+
+/// Twice a gap and the sizes next to it still fit in a u16.
+pub const MAX_GAP: u16 = u16::MAX / 4;
+
 #[derive(Clone, Deserialize)]
 pub struct GapsConfig {
     inner_size: u16,
@@ -31,40 +232,162 @@ pub struct GapsConfig {
 
 pub enum LoadingConfigError {
     TomlParsingError(Error),
+    IoError(std::io::Error),
+    /// Every window and monitor needs a tag to be on.
+    NoTags,
 }
 
 pub fn print_error(error: LoadingConfigError) {
     match error {
         LoadingConfigError::TomlParsingError(error) => eprintln!("Error {}", error.message()),
+        LoadingConfigError::IoError(error) => eprintln!("Error {}", error),
+        LoadingConfigError::NoTags => eprintln!("Error `tags` can't be empty"),
     }
 }
 
 impl Config {
     pub fn load() -> Result<Config, LoadingConfigError> {
-        let home_dir = env!("HOME");
-        let path_to_config = format!("{}/.config/xrwm/Config.toml", home_dir);
-        let mut file = match File::open(path_to_config) {
+        let home_dir = std::env::var("HOME").unwrap_or_default();
+        let path_to_config_dir = format!("{}/.config/xrwm", home_dir);
+        let path_to_config = format!("{}/Config.toml", path_to_config_dir);
+        let mut file = match File::open(&path_to_config) {
             Ok(file) => file,
-            Err(err) => {
-                create_dir(".config/xrwm");
-                File::create(path_to_config).expect("can't create file")
+            Err(_) => {
+                create_dir_all(&path_to_config_dir).map_err(LoadingConfigError::IoError)?;
+                File::create(&path_to_config).map_err(LoadingConfigError::IoError)?;
+                return Ok(Config::default());
             }
         };
         let mut config_string = String::new();
-        file.read_to_string(&mut config_string);
-        match toml::from_str(&config_string) {
-            Ok(config) => Ok(config),
-            Err(error) => Err(LoadingConfigError::TomlParsingError(error)),
+        file.read_to_string(&mut config_string)
+            .map_err(LoadingConfigError::IoError)?;
+        Config::parse(&config_string)
+    }
+
+    fn parse(config_string: &str) -> Result<Config, LoadingConfigError> {
+        let config: Config = match toml::from_str(config_string) {
+            Ok(config) => config,
+            Err(error) => return Err(LoadingConfigError::TomlParsingError(error)),
+        };
+        if config.tags.is_empty() {
+            return Err(LoadingConfigError::NoTags);
         }
+        Ok(config)
+    }
+
+    pub fn tags(&self) -> Vec<String> {
+        self.tags.clone()
     }
 
     pub fn tiling_config(&self) -> TilingConfig {
         self.tiling_config.clone()
     }
+
+    pub fn window_config(&self) -> WindowConfig {
+        self.window_config.clone()
+    }
+
+    pub fn focus_config(&self) -> FocusConfig {
+        self.focus_config.clone()
+    }
+
+    pub fn monitor_tags(&self) -> MonitorTags {
+        self.monitor_tags
+    }
+
+    /// Layout a tag starts with.
+    pub fn tag_layout(&self, tag_name: &str) -> LayoutKind {
+        self.tag_layouts
+            .get(tag_name)
+            .copied()
+            .unwrap_or(self.layout)
+    }
+}
+
+impl WindowConfig {
+    pub fn border_size(&self) -> u16 {
+        self.border_size
+    }
+
+    pub fn open_in_center_on_floating_mode(&self) -> bool {
+        self.open_in_center_on_floating_mode
+    }
+
+    pub fn close_timeout(&self) -> Option<std::time::Duration> {
+        self.close_timeout.map(std::time::Duration::from_millis)
+    }
+
+    pub fn mouse_modifier(&self) -> MouseModifier {
+        self.mouse_modifier
+    }
 }
 
 impl TilingConfig {
     pub fn gaps_config(&self) -> Option<GapsConfig> {
         self.gaps_config.clone()
     }
+
+    pub fn master_count(&self) -> usize {
+        self.master_count
+    }
+
+    pub fn master_ratio(&self) -> f32 {
+        self.master_ratio
+    }
+
+    pub fn master_position(&self) -> MasterPosition {
+        self.master_position
+    }
+
+    pub fn smart_borders(&self) -> bool {
+        self.smart_borders
+    }
+
+    pub fn smart_gaps(&self) -> bool {
+        self.smart_gaps
+    }
+
+    pub fn column_width(&self) -> f32 {
+        self.column_width
+    }
+}
+
+impl GapsConfig {
+    /// Gap between two windows.
+    pub fn inner_size(&self) -> u16 {
+        self.inner_size.min(MAX_GAP)
+    }
+
+    /// Gap between the windows and the edges of the area.
+    pub fn outer_size(&self) -> u16 {
+        self.outer_size.min(MAX_GAP)
+    }
+}
+
+impl FocusConfig {
+    pub fn focus_mode(&self) -> FocusMode {
+        self.focus_mode
+    }
+
+    pub fn focus_stealing(&self) -> FocusStealing {
+        self.focus_stealing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_tags_are_rejected() {
+        assert!(matches!(
+            Config::parse("tags = []"),
+            Err(LoadingConfigError::NoTags)
+        ));
+        assert_eq!(
+            Config::parse("tags = [\"web\"]").ok().unwrap().tags(),
+            ["web"]
+        );
+        assert_eq!(Config::parse("").ok().unwrap().tags().len(), 9);
+    }
 }
@@ -1,12 +1,19 @@
 use config::*;
 use window_manager::*;
 
+pub mod atoms;
 pub mod config;
 pub mod screen;
 pub mod window;
 pub mod window_manager;
 
 fn main() {
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+    if arguments.first().map(String::as_str) == Some("msg") {
+        send_message(&arguments[1..].join(" "));
+        return;
+    }
+
     let config = match Config::load() {
         Ok(config) => config,
         Err(error) => {
@@ -15,8 +22,38 @@ fn main() {
         }
     };
 
-    let window_manager = WindowManager::new(config);
+    let window_manager = match WindowManager::new(config, restored_state(&arguments)) {
+        Ok(window_manager) => window_manager,
+        Err(error) => {
+            print_runtime_error(error);
+            return;
+        }
+    };
     if let Err(error) = window_manager.run() {
         print_runtime_error(error)
     }
 }
+
+/// Reads the state passed with `--restore <path>` by an in-place restart.
+fn restored_state(arguments: &[String]) -> Option<restart::State> {
+    let index = arguments
+        .iter()
+        .position(|argument| argument == "--restore")?;
+    let path = std::path::Path::new(arguments.get(index + 1)?);
+    let state = restart::State::load(path);
+    let _ = std::fs::remove_file(path);
+    match state {
+        Ok(state) => Some(state),
+        Err(error) => {
+            eprintln!("Error {}", error);
+            None
+        }
+    }
+}
+
+fn send_message(command: &str) {
+    match ipc::send_command(command) {
+        Ok(answer) => println!("{}", answer),
+        Err(error) => eprintln!("Error {}", error),
+    }
+}
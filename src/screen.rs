@@ -1,14 +1,275 @@
-use std::sync::Arc;
+//! Monitors come from RandR 1.5, or from Xinerama on servers without it.
+//! Both can be tried in a nested server, e.g.
+//! `Xephyr +xinerama -screen 800x600 -screen 800x600 :1`, or with
+//! `xrandr --setmonitor` on Xvfb.
 
-use xcb::Connection;
+use xcb::{randr, x, xinerama, Connection};
 
+use crate::{config::MonitorTags, window::WindowGeometry};
+
+/// Part of the screen shown by an output. Every monitor shows one tag.
+pub struct Monitor {
+    name: String,
+    geometry: WindowGeometry,
+    /// The geometry without the space reserved by docks.
+    usable_area: WindowGeometry,
+    tag: u32,
+}
+
+impl Monitor {
+    pub fn new(name: String, geometry: WindowGeometry, tag: u32) -> Monitor {
+        Monitor {
+            name,
+            geometry,
+            usable_area: geometry,
+            tag,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn geometry(&self) -> WindowGeometry {
+        self.geometry
+    }
+
+    pub fn usable_area(&self) -> WindowGeometry {
+        self.usable_area
+    }
+
+    pub fn set_usable_area(&mut self, usable_area: WindowGeometry) {
+        self.usable_area = usable_area;
+    }
+
+    pub fn tag(&self) -> u32 {
+        self.tag
+    }
+
+    pub fn set_tag(&mut self, tag: u32) {
+        self.tag = tag;
+    }
+
+    fn contains(&self, x: i16, y: i16) -> bool {
+        let geometry = self.geometry;
+        (geometry.x()..geometry.x().saturating_add_unsigned(geometry.width())).contains(&x)
+            && (geometry.y()..geometry.y().saturating_add_unsigned(geometry.height())).contains(&y)
+    }
+}
+
+/// The root window and the monitors, from RandR 1.5 or Xinerama. Without
+/// either, the whole screen is one monitor.
 pub struct Screen {
-    connection: Arc<Connection>,
-    screen: xcb::x::Screen,
+    root: x::Window,
+    width: u16,
+    height: u16,
+    monitors: Vec<Monitor>,
+    focused: usize,
 }
 
 impl Screen {
-    pub fn new(connection: Connection) -> Screen {
-        f
+    /// Monitors show the first tags, in the order the server lists them. With
+    /// tags per monitor, every monitor shows its first tag.
+    pub fn new(
+        connection: &Connection,
+        screen_number: i32,
+        tags: u32,
+        monitor_tags: MonitorTags,
+    ) -> Screen {
+        let screen = connection
+            .get_setup()
+            .roots()
+            .nth(screen_number as usize)
+            .expect("screen number is out of range");
+        let mut screen = Screen {
+            root: screen.root(),
+            width: screen.width_in_pixels(),
+            height: screen.height_in_pixels(),
+            monitors: Vec::new(),
+            focused: 0,
+        };
+        screen.update(connection, tags, monitor_tags);
+        screen
+    }
+
+    pub fn root(&self) -> x::Window {
+        self.root
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn geometry(&self) -> WindowGeometry {
+        WindowGeometry::new(0, 0, self.width, self.height)
+    }
+
+    pub fn monitors(&self) -> &[Monitor] {
+        &self.monitors
+    }
+
+    pub fn monitors_mut(&mut self) -> &mut [Monitor] {
+        &mut self.monitors
+    }
+
+    pub fn focused(&self) -> usize {
+        self.focused
+    }
+
+    pub fn focused_monitor(&self) -> &Monitor {
+        &self.monitors[self.focused]
+    }
+
+    pub fn focused_monitor_mut(&mut self) -> &mut Monitor {
+        &mut self.monitors[self.focused]
+    }
+
+    pub fn focus_monitor(&mut self, index: usize) {
+        if index < self.monitors.len() {
+            self.focused = index;
+        }
+    }
+
+    /// Monitor showing the point, in root coordinates.
+    pub fn monitor_at(&self, x: i16, y: i16) -> Option<usize> {
+        self.monitors
+            .iter()
+            .position(|monitor| monitor.contains(x, y))
+    }
+
+    /// Asks for `RRScreenChangeNotify` when outputs are added, removed or
+    /// change their mode.
+    pub fn select_changes(&self, connection: &Connection) {
+        if has_extension(connection, xcb::Extension::RandR) {
+            connection.send_request(&randr::SelectInput {
+                window: self.root,
+                enable: randr::NotifyMask::SCREEN_CHANGE,
+            });
+        }
+    }
+
+    /// Reads the size of the screen and the monitors again. Monitors that are
+    /// still there keep their tag, new ones show the first tag no other
+    /// monitor shows, or their own first tag with tags per monitor.
+    pub fn update(&mut self, connection: &Connection, tags: u32, monitor_tags: MonitorTags) {
+        let cookie = connection.send_request(&x::GetGeometry {
+            drawable: x::Drawable::Window(self.root),
+        });
+        if let Ok(geometry) = connection.wait_for_reply(cookie) {
+            self.width = geometry.width();
+            self.height = geometry.height();
+        }
+        let outputs = randr_monitors(connection, self.root)
+            .or_else(|| xinerama_screens(connection))
+            .filter(|outputs| !outputs.is_empty())
+            .unwrap_or_else(|| vec![("default".to_string(), self.geometry())]);
+        let focused = self
+            .monitors
+            .get(self.focused)
+            .map(|monitor| monitor.name.clone());
+        let mut old = std::mem::take(&mut self.monitors);
+        for (name, geometry) in outputs {
+            let tag = match old.iter().position(|monitor| monitor.name == name) {
+                Some(index) => old.remove(index).tag,
+                None if monitor_tags == MonitorTags::PerMonitor => 1,
+                None => (1..=tags)
+                    .find(|tag| {
+                        !self.monitors.iter().any(|monitor| monitor.tag == *tag)
+                            && !old.iter().any(|monitor| monitor.tag == *tag)
+                    })
+                    .unwrap_or(tags.max(1)),
+            };
+            self.monitors.push(Monitor::new(name, geometry, tag));
+        }
+        self.focused = focused
+            .and_then(|name| {
+                self.monitors
+                    .iter()
+                    .position(|monitor| monitor.name == name)
+            })
+            .unwrap_or(0);
+    }
+}
+
+fn has_extension(connection: &Connection, extension: xcb::Extension) -> bool {
+    connection
+        .active_extensions()
+        .any(|active| active == extension)
+}
+
+/// Monitors of RandR 1.5, `None` if the server doesn't support them.
+fn randr_monitors(
+    connection: &Connection,
+    root: x::Window,
+) -> Option<Vec<(String, WindowGeometry)>> {
+    if !has_extension(connection, xcb::Extension::RandR) {
+        return None;
+    }
+    let cookie = connection.send_request(&randr::QueryVersion {
+        major_version: 1,
+        minor_version: 5,
+    });
+    let version = connection.wait_for_reply(cookie).ok()?;
+    if (version.major_version(), version.minor_version()) < (1, 5) {
+        return None;
+    }
+    let cookie = connection.send_request(&randr::GetMonitors {
+        window: root,
+        get_active: true,
+    });
+    let reply = connection.wait_for_reply(cookie).ok()?;
+    let cookies: Vec<_> = reply
+        .monitors()
+        .map(|monitor| {
+            let geometry =
+                WindowGeometry::new(monitor.x(), monitor.y(), monitor.width(), monitor.height());
+            let name = connection.send_request(&x::GetAtomName {
+                atom: monitor.name(),
+            });
+            (name, geometry)
+        })
+        .collect();
+    Some(
+        cookies
+            .into_iter()
+            .enumerate()
+            .map(|(index, (name, geometry))| {
+                let name = connection
+                    .wait_for_reply(name)
+                    .map(|name| name.name().to_utf8().into_owned())
+                    .unwrap_or_else(|_| format!("monitor-{}", index));
+                (name, geometry)
+            })
+            .collect(),
+    )
+}
+
+/// Screens of Xinerama, `None` if it isn't active.
+fn xinerama_screens(connection: &Connection) -> Option<Vec<(String, WindowGeometry)>> {
+    if !has_extension(connection, xcb::Extension::Xinerama) {
+        return None;
+    }
+    let cookie = connection.send_request(&xinerama::IsActive {});
+    if connection.wait_for_reply(cookie).ok()?.state() == 0 {
+        return None;
     }
+    let cookie = connection.send_request(&xinerama::QueryScreens {});
+    let reply = connection.wait_for_reply(cookie).ok()?;
+    Some(
+        reply
+            .screen_info()
+            .iter()
+            .enumerate()
+            .map(|(index, screen)| {
+                (
+                    format!("xinerama-{}", index),
+                    WindowGeometry::new(screen.x_org, screen.y_org, screen.width, screen.height),
+                )
+            })
+            .collect(),
+    )
 }
@@ -1,11 +1,49 @@
 use std::{cell::RefCell, sync::Arc};
-use xcb::x::ConfigWindow;
+
+use serde::{Deserialize, Serialize};
+use xcb::{
+    x::{ConfigWindow, PropEl},
+    Xid,
+};
+
+use crate::{atoms::Atoms, window_manager::ewmh::tag_to_desktop};
+
+use self::{
+    ewmh::{NetWmState, WindowType, ALL_DESKTOPS},
+    icccm::{decode_text, InputModel, SizeHints, TextEncoding, WmClass, WmHints},
+};
+
+pub mod ewmh;
+pub mod icccm;
 
 pub struct XWindow {
     window: xcb::x::Window,
     connection: Arc<xcb::Connection>,
+    atoms: Arc<Atoms>,
     current_tag: RefCell<u32>,
+    /// Monitor whose tags the window is on, if every monitor has its own.
+    monitor: RefCell<Option<String>>,
     allowed_tags: TagRules,
+    mapped: RefCell<bool>,
+    expected_unmaps: RefCell<u32>,
+    properties: RefCell<Properties>,
+    states: RefCell<Vec<NetWmState>>,
+    /// Set by the user, overrides the window type.
+    floating: RefCell<Option<bool>>,
+    floating_geometry: RefCell<Option<WindowGeometry>>,
+}
+
+/// Client properties, `None` until read for the first time or after the
+/// property changed.
+#[derive(Default)]
+struct Properties {
+    name: Option<String>,
+    wm_class: Option<WmClass>,
+    wm_hints: Option<WmHints>,
+    size_hints: Option<SizeHints>,
+    transient_for: Option<Option<xcb::x::Window>>,
+    protocols: Option<Vec<xcb::x::Atom>>,
+    window_type: Option<WindowType>,
 }
 
 #[derive(Clone)]
@@ -21,6 +59,10 @@ pub struct WindowPosition {
 }
 
 impl WindowPosition {
+    pub fn new(x: i16, y: i16) -> WindowPosition {
+        WindowPosition { x, y }
+    }
+
     pub fn x(&self) -> i16 {
         self.x
     }
@@ -37,6 +79,10 @@ pub struct WindowSize {
 }
 
 impl WindowSize {
+    pub fn new(width: u16, height: u16) -> WindowSize {
+        WindowSize { height, width }
+    }
+
     pub fn height(&self) -> u16 {
         self.height
     }
@@ -46,7 +92,50 @@ impl WindowSize {
     }
 }
 
-trait Window {
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    x: i16,
+    y: i16,
+    width: u16,
+    height: u16,
+}
+
+impl WindowGeometry {
+    pub fn new(x: i16, y: i16, width: u16, height: u16) -> WindowGeometry {
+        WindowGeometry {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn x(&self) -> i16 {
+        self.x
+    }
+
+    pub fn y(&self) -> i16 {
+        self.y
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn position(&self) -> WindowPosition {
+        WindowPosition::new(self.x, self.y)
+    }
+
+    pub fn size(&self) -> WindowSize {
+        WindowSize::new(self.width, self.height)
+    }
+}
+
+pub trait Window {
     //Window size
     fn window_size(&self) -> Result<WindowSize, xcb::Error>;
     fn resize_window(&self, window_size: WindowSize) -> Result<(), xcb::ConnError>;
@@ -56,6 +145,9 @@ trait Window {
     fn change_window_position(&self, window_position: WindowPosition)
         -> Result<(), xcb::ConnError>;
 
+    //Geometry
+    fn change_geometry(&self, geometry: WindowGeometry) -> Result<(), xcb::ConnError>;
+
     //Border
     fn border_size(&self) -> Result<u16, xcb::Error>;
     fn change_border_size(&self, border_size: u16) -> Result<(), xcb::ConnError>;
@@ -65,34 +157,423 @@ trait Window {
     fn current_tag(&self) -> u32;
     fn allowed_tags(&self) -> TagRules;
 
+    /// Mapping and unmapping aren't flushed, the layout is applied at once.
     fn show(&self) -> Result<(), xcb::ConnError>;
     fn hide(&self) -> Result<(), xcb::ConnError>;
-    fn close(self) -> Result<(), xcb::ConnError>;
+    fn close(&self) -> Result<(), xcb::ConnError>;
+    fn kill(&self) -> Result<(), xcb::ConnError>;
 }
 
 impl XWindow {
     pub fn new(
         window: xcb::x::Window,
         connection: Arc<xcb::Connection>,
+        atoms: Arc<Atoms>,
         current_tag: u32,
         allowed_tags: TagRules,
     ) -> Self {
         let window = XWindow {
             window,
             connection,
+            atoms,
             current_tag: RefCell::new(current_tag),
+            monitor: RefCell::new(None),
             allowed_tags,
+            mapped: RefCell::new(false),
+            expected_unmaps: RefCell::new(0),
+            properties: RefCell::new(Properties::default()),
+            states: RefCell::new(Vec::new()),
+            floating: RefCell::new(None),
+            floating_geometry: RefCell::new(None),
         };
+        *window.states.borrow_mut() = window
+            .property::<xcb::x::Atom>(window.atoms.net_wm_state, xcb::x::ATOM_ATOM, 32)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|atom| NetWmState::from_atom(atom, &window.atoms))
+            .collect();
         window.move_to(current_tag);
         window
     }
 
-    fn window(&self) -> xcb::x::Window {
-        self.window.clone()
+    pub fn window(&self) -> xcb::x::Window {
+        self.window
+    }
+
+    /// Consumes one unmap that was requested by the window manager itself,
+    /// returns `false` if the unmap came from the client.
+    pub fn take_expected_unmap(&self) -> bool {
+        let mut expected_unmaps = self.expected_unmaps.borrow_mut();
+        if *expected_unmaps == 0 {
+            return false;
+        }
+        *expected_unmaps -= 1;
+        true
+    }
+
+    pub fn is_mapped(&self) -> bool {
+        *self.mapped.borrow()
+    }
+
+    pub fn geometry(&self) -> Result<WindowGeometry, xcb::Error> {
+        let cookie = self.connection.send_request(&xcb::x::GetGeometry {
+            drawable: xcb::x::Drawable::Window(self.window()),
+        });
+        let responce = self.connection.wait_for_reply(cookie)?;
+        Ok(WindowGeometry::new(
+            responce.x(),
+            responce.y(),
+            responce.width(),
+            responce.height(),
+        ))
+    }
+
+    /// Geometry of the window while it floats. Until it's placed, moved or
+    /// resized, it's the geometry the window had when it was managed.
+    pub fn floating_geometry(&self) -> Result<WindowGeometry, xcb::Error> {
+        if let Some(geometry) = *self.floating_geometry.borrow() {
+            return Ok(geometry);
+        }
+        let geometry = self.geometry()?;
+        self.set_floating_geometry(geometry);
+        Ok(geometry)
+    }
+
+    /// Floating geometry if the window was floating, without asking the
+    /// server.
+    pub fn saved_floating_geometry(&self) -> Option<WindowGeometry> {
+        *self.floating_geometry.borrow()
+    }
+
+    pub fn set_floating_geometry(&self, geometry: WindowGeometry) {
+        *self.floating_geometry.borrow_mut() = Some(geometry);
+    }
+
+    /// Moves, resizes and sets the border of the window in one request,
+    /// without flushing the connection.
+    pub fn configure(&self, geometry: WindowGeometry, border_size: u16) {
+        self.connection.send_request(&xcb::x::ConfigureWindow {
+            window: self.window(),
+            value_list: &[
+                ConfigWindow::X(geometry.x() as i32),
+                ConfigWindow::Y(geometry.y() as i32),
+                ConfigWindow::Width(geometry.width() as u32),
+                ConfigWindow::Height(geometry.height() as u32),
+                ConfigWindow::BorderWidth(border_size as u32),
+            ],
+        });
+    }
+
+    /// Tells the client its current geometry with a synthetic
+    /// `ConfigureNotify`, the answer to a configure request that isn't
+    /// granted.
+    pub fn send_configure_notify(&self) -> Result<(), xcb::Error> {
+        let cookie = self.connection.send_request(&xcb::x::GetGeometry {
+            drawable: xcb::x::Drawable::Window(self.window()),
+        });
+        let responce = self.connection.wait_for_reply(cookie)?;
+        let event = xcb::x::ConfigureNotifyEvent::new(
+            self.window(),
+            self.window(),
+            xcb::x::WINDOW_NONE,
+            responce.x(),
+            responce.y(),
+            responce.width(),
+            responce.height(),
+            responce.border_width(),
+            false,
+        );
+        self.connection.send_request(&xcb::x::SendEvent {
+            propagate: false,
+            destination: xcb::x::SendEventDest::Window(self.window()),
+            event_mask: xcb::x::EventMask::STRUCTURE_NOTIFY,
+            event: &event,
+        });
+        Ok(())
+    }
+
+    //ICCCM properties
+    /// `_NET_WM_NAME`, or `WM_NAME` for clients that don't set it.
+    pub fn name(&self) -> String {
+        if let Some(name) = &self.properties.borrow().name {
+            return name.clone();
+        }
+        let name = self
+            .property::<u8>(self.atoms.net_wm_name, self.atoms.utf8_string, 256)
+            .filter(|name| !name.is_empty())
+            .map(|name| decode_text(&name, TextEncoding::Utf8))
+            .or_else(|| self.text_property(xcb::x::ATOM_WM_NAME))
+            .unwrap_or_default();
+        self.properties.borrow_mut().name = Some(name.clone());
+        name
+    }
+
+    pub fn wm_class(&self) -> WmClass {
+        if let Some(wm_class) = &self.properties.borrow().wm_class {
+            return wm_class.clone();
+        }
+        let wm_class = self
+            .property::<u8>(xcb::x::ATOM_WM_CLASS, xcb::x::ATOM_STRING, 256)
+            .map(|value| WmClass::parse(&value))
+            .unwrap_or_default();
+        self.properties.borrow_mut().wm_class = Some(wm_class.clone());
+        wm_class
+    }
+
+    pub fn wm_hints(&self) -> WmHints {
+        if let Some(wm_hints) = self.properties.borrow().wm_hints {
+            return wm_hints;
+        }
+        let wm_hints = self
+            .property::<u32>(xcb::x::ATOM_WM_HINTS, xcb::x::ATOM_WM_HINTS, 9)
+            .map(|value| WmHints::parse(&value))
+            .unwrap_or_default();
+        self.properties.borrow_mut().wm_hints = Some(wm_hints);
+        wm_hints
+    }
+
+    pub fn size_hints(&self) -> SizeHints {
+        if let Some(size_hints) = self.properties.borrow().size_hints {
+            return size_hints;
+        }
+        let size_hints = self
+            .property::<u32>(xcb::x::ATOM_WM_NORMAL_HINTS, xcb::x::ATOM_WM_SIZE_HINTS, 18)
+            .map(|value| SizeHints::parse(&value))
+            .unwrap_or_default();
+        self.properties.borrow_mut().size_hints = Some(size_hints);
+        size_hints
+    }
+
+    pub fn transient_for(&self) -> Option<xcb::x::Window> {
+        if let Some(transient_for) = self.properties.borrow().transient_for {
+            return transient_for;
+        }
+        let transient_for = self
+            .property::<xcb::x::Window>(xcb::x::ATOM_WM_TRANSIENT_FOR, xcb::x::ATOM_WINDOW, 1)
+            .and_then(|value| value.first().copied());
+        self.properties.borrow_mut().transient_for = Some(transient_for);
+        transient_for
+    }
+
+    /// Atoms listed in `WM_PROTOCOLS`, e.g. `WM_DELETE_WINDOW`.
+    pub fn protocols(&self) -> Vec<xcb::x::Atom> {
+        if let Some(protocols) = &self.properties.borrow().protocols {
+            return protocols.clone();
+        }
+        let protocols = self
+            .property::<xcb::x::Atom>(self.atoms.wm_protocols, xcb::x::ATOM_ATOM, 32)
+            .unwrap_or_default();
+        self.properties.borrow_mut().protocols = Some(protocols.clone());
+        protocols
+    }
+
+    pub fn supports_protocol(&self, protocol: xcb::x::Atom) -> bool {
+        self.protocols().contains(&protocol)
+    }
+
+    pub fn input_model(&self) -> InputModel {
+        InputModel::new(
+            self.wm_hints().input(),
+            self.supports_protocol(self.atoms.wm_take_focus),
+        )
+    }
+
+    /// Sends `WM_TAKE_FOCUS`, used for locally and globally active clients.
+    /// `time` is the time of the event that moved the focus, ICCCM 4.1.7
+    /// doesn't allow `CurrentTime`.
+    pub fn take_focus(&self, time: xcb::x::Timestamp) -> Result<(), xcb::ConnError> {
+        self.send_protocol(self.atoms.wm_take_focus, time)
+    }
+
+    pub fn window_type(&self) -> WindowType {
+        if let Some(window_type) = self.properties.borrow().window_type {
+            return window_type;
+        }
+        let window_type = WindowType::read(&self.connection, &self.atoms, self.window);
+        self.properties.borrow_mut().window_type = Some(window_type);
+        window_type
+    }
+
+    /// Dialogs, splash screens, utility windows and transients aren't tiled,
+    /// unless the user toggled them.
+    pub fn is_floating(&self) -> bool {
+        if let Some(floating) = *self.floating.borrow() {
+            return floating;
+        }
+        self.window_type().is_floating() || self.transient_for().is_some()
+    }
+
+    pub fn set_floating(&self, floating: bool) {
+        *self.floating.borrow_mut() = Some(floating);
+    }
+
+    /// `None` unless the user toggled the window.
+    pub fn floating_override(&self) -> Option<bool> {
+        *self.floating.borrow()
+    }
+
+    /// `_NET_WM_PID`, only if `WM_CLIENT_MACHINE` is this host, since the pid
+    /// means nothing on another machine. Pids that would signal more than
+    /// one process, or the window manager, are ignored.
+    pub fn local_pid(&self) -> Option<u32> {
+        let machine =
+            self.property::<u8>(xcb::x::ATOM_WM_CLIENT_MACHINE, xcb::x::ATOM_STRING, 64)?;
+        if machine != hostname()? {
+            return None;
+        }
+        self.property::<u32>(self.atoms.net_wm_pid, xcb::x::ATOM_CARDINAL, 1)?
+            .first()
+            .copied()
+            .filter(|pid| is_client_pid(*pid))
+    }
+
+    /// Sends SIGTERM to the client process, returns `false` if its pid is
+    /// unknown.
+    pub fn terminate(&self) -> bool {
+        match self.local_pid() {
+            Some(pid) => unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) == 0 },
+            None => false,
+        }
+    }
+
+    /// Drops the cached value of a changed property, it's read again on the
+    /// next access.
+    pub fn refresh_property(&self, property: xcb::x::Atom) {
+        let mut properties = self.properties.borrow_mut();
+        match property {
+            xcb::x::ATOM_WM_NAME => properties.name = None,
+            xcb::x::ATOM_WM_CLASS => properties.wm_class = None,
+            xcb::x::ATOM_WM_HINTS => properties.wm_hints = None,
+            xcb::x::ATOM_WM_NORMAL_HINTS => properties.size_hints = None,
+            xcb::x::ATOM_WM_TRANSIENT_FOR => {
+                properties.transient_for = None;
+                properties.window_type = None;
+            }
+            property if property == self.atoms.net_wm_name => properties.name = None,
+            property if property == self.atoms.wm_protocols => properties.protocols = None,
+            property if property == self.atoms.net_wm_window_type => properties.window_type = None,
+            _ => {}
+        }
+    }
+
+    /// Sends a `WM_PROTOCOLS` client message to the window.
+    fn send_protocol(
+        &self,
+        protocol: xcb::x::Atom,
+        time: xcb::x::Timestamp,
+    ) -> Result<(), xcb::ConnError> {
+        let event = xcb::x::ClientMessageEvent::new(
+            self.window(),
+            self.atoms.wm_protocols,
+            xcb::x::ClientMessageData::Data32([protocol.resource_id(), time, 0, 0, 0]),
+        );
+        self.connection.send_request(&xcb::x::SendEvent {
+            propagate: false,
+            destination: xcb::x::SendEventDest::Window(self.window()),
+            event_mask: xcb::x::EventMask::NO_EVENT,
+            event: &event,
+        });
+        self.connection.flush()
     }
 
+    /// Text property in any of the encodings ICCCM allows.
+    fn text_property(&self, property: xcb::x::Atom) -> Option<String> {
+        let cookie = self.connection.send_request(&xcb::x::GetProperty {
+            delete: false,
+            window: self.window,
+            property,
+            r#type: xcb::x::ATOM_ANY,
+            long_offset: 0,
+            long_length: 256,
+        });
+        let reply = self.connection.wait_for_reply(cookie).ok()?;
+        let encoding = match reply.r#type() {
+            xcb::x::ATOM_STRING => TextEncoding::Latin1,
+            r#type if r#type == self.atoms.utf8_string => TextEncoding::Utf8,
+            r#type if r#type == self.atoms.compound_text => TextEncoding::CompoundText,
+            _ => return None,
+        };
+        if reply.format() != 8 {
+            return None;
+        }
+        Some(decode_text(reply.value::<u8>(), encoding))
+    }
+
+    fn property<P: PropEl + Clone>(
+        &self,
+        property: xcb::x::Atom,
+        r#type: xcb::x::Atom,
+        length: u32,
+    ) -> Option<Vec<P>> {
+        read_property(&self.connection, self.window, property, r#type, length)
+    }
+
+    //EWMH state
+    pub fn has_state(&self, state: NetWmState) -> bool {
+        self.states.borrow().contains(&state)
+    }
+
+    /// Adds or removes the state and updates `_NET_WM_STATE`.
+    pub fn set_state(&self, state: NetWmState, enabled: bool) {
+        if self.has_state(state) == enabled {
+            return;
+        }
+        let mut states = self.states.borrow_mut();
+        match enabled {
+            true => states.push(state),
+            false => states.retain(|current| *current != state),
+        }
+        let atoms: Vec<xcb::x::Atom> = states.iter().map(|state| state.atom(&self.atoms)).collect();
+        drop(states);
+        self.connection.send_request(&xcb::x::ChangeProperty {
+            mode: xcb::x::PropMode::Replace,
+            window: self.window,
+            property: self.atoms.net_wm_state,
+            r#type: xcb::x::ATOM_ATOM,
+            data: &atoms,
+        });
+        if state == NetWmState::Sticky {
+            self.update_desktop();
+        }
+    }
+
+    pub fn monitor(&self) -> Option<String> {
+        self.monitor.borrow().clone()
+    }
+
+    pub fn set_monitor(&self, monitor: Option<String>) {
+        *self.monitor.borrow_mut() = monitor;
+    }
+
+    /// Sticky windows are shown on every tag, hidden ones on none.
+    pub fn is_visible_on(&self, tag: u32) -> bool {
+        !self.has_state(NetWmState::Hidden)
+            && (self.current_tag() == tag || self.has_state(NetWmState::Sticky))
+    }
+
+    /// Also stores the tag in `_XRWM_TAG`, so it survives a restart.
     fn set_current_tag(&self, new_tag: u32) {
-        *(self.current_tag).borrow_mut() = new_tag
+        *(self.current_tag).borrow_mut() = new_tag;
+        self.set_cardinal(self.atoms.xrwm_tag, new_tag);
+        self.update_desktop();
+    }
+
+    fn update_desktop(&self) {
+        let desktop = match self.has_state(NetWmState::Sticky) {
+            true => ALL_DESKTOPS,
+            false => tag_to_desktop(self.current_tag()),
+        };
+        self.set_cardinal(self.atoms.net_wm_desktop, desktop);
+    }
+
+    fn set_cardinal(&self, property: xcb::x::Atom, value: u32) {
+        self.connection.send_request(&xcb::x::ChangeProperty {
+            mode: xcb::x::PropMode::Replace,
+            window: self.window,
+            property,
+            r#type: xcb::x::ATOM_CARDINAL,
+            data: &[value],
+        });
     }
 }
 
@@ -110,12 +591,12 @@ impl Window for XWindow {
     }
 
     fn resize_window(&self, window_size: WindowSize) -> Result<(), xcb::ConnError> {
+        let (width, height) = self
+            .size_hints()
+            .constrain(window_size.width() as u32, window_size.height() as u32);
         self.connection.send_request(&xcb::x::ConfigureWindow {
             window: self.window(),
-            value_list: &[
-                ConfigWindow::Height(window_size.height() as u32),
-                ConfigWindow::Width(window_size.width() as u32),
-            ],
+            value_list: &[ConfigWindow::Width(width), ConfigWindow::Height(height)],
         });
         self.connection.flush()
     }
@@ -146,6 +627,20 @@ impl Window for XWindow {
         self.connection.flush()
     }
 
+    //Geometry
+    fn change_geometry(&self, geometry: WindowGeometry) -> Result<(), xcb::ConnError> {
+        self.connection.send_request(&xcb::x::ConfigureWindow {
+            window: self.window(),
+            value_list: &[
+                ConfigWindow::X(geometry.x() as i32),
+                ConfigWindow::Y(geometry.y() as i32),
+                ConfigWindow::Width(geometry.width() as u32),
+                ConfigWindow::Height(geometry.height() as u32),
+            ],
+        });
+        self.connection.flush()
+    }
+
     //Border size
     fn border_size(&self) -> Result<u16, xcb::Error> {
         let cookie = self.connection.send_request(&xcb::x::GetGeometry {
@@ -187,23 +682,95 @@ impl Window for XWindow {
     }
 
     fn show(&self) -> Result<(), xcb::ConnError> {
+        *self.mapped.borrow_mut() = true;
         self.connection.send_request(&xcb::x::MapWindow {
             window: self.window(),
         });
-        self.connection.flush()
+        Ok(())
     }
 
     fn hide(&self) -> Result<(), xcb::ConnError> {
+        if !self.is_mapped() {
+            return Ok(());
+        }
+        *self.mapped.borrow_mut() = false;
+        *self.expected_unmaps.borrow_mut() += 1;
         self.connection.send_request(&xcb::x::UnmapWindow {
             window: self.window(),
         });
-        self.connection.flush()
+        Ok(())
     }
 
-    fn close(self) -> Result<(), xcb::ConnError> {
-        self.connection.send_request(&xcb::x::DestroyWindow {
-            window: self.window(),
+    /// Asks the client to close the window with `WM_DELETE_WINDOW`, clients
+    /// that don't support it are killed.
+    fn close(&self) -> Result<(), xcb::ConnError> {
+        if !self.supports_protocol(self.atoms.wm_delete_window) {
+            return self.kill();
+        }
+        self.send_protocol(self.atoms.wm_delete_window, xcb::x::CURRENT_TIME)
+    }
+
+    fn kill(&self) -> Result<(), xcb::ConnError> {
+        self.connection.send_request(&xcb::x::KillClient {
+            resource: self.window().resource_id(),
         });
         self.connection.flush()
     }
 }
+
+/// Reads up to `length` 32 bit units of a property, `None` if the property is
+/// missing or has another type or format.
+pub fn read_property<P: PropEl + Clone>(
+    connection: &xcb::Connection,
+    window: xcb::x::Window,
+    property: xcb::x::Atom,
+    r#type: xcb::x::Atom,
+    length: u32,
+) -> Option<Vec<P>> {
+    let cookie = connection.send_request(&xcb::x::GetProperty {
+        delete: false,
+        window,
+        property,
+        r#type,
+        long_offset: 0,
+        long_length: length,
+    });
+    let reply = connection.wait_for_reply(cookie).ok()?;
+    if reply.r#type() == xcb::x::ATOM_NONE || reply.format() != P::FORMAT {
+        return None;
+    }
+    if r#type != xcb::x::ATOM_ANY && reply.r#type() != r#type {
+        return None;
+    }
+    Some(reply.value::<P>().to_vec())
+}
+
+/// `kill` takes 0 and negative pids as process groups, and 1 is init.
+fn is_client_pid(pid: u32) -> bool {
+    pid > 1 && pid <= i32::MAX as u32 && pid != std::process::id()
+}
+
+fn hostname() -> Option<Vec<u8>> {
+    let mut buffer = [0u8; 256];
+    if unsafe { libc::gethostname(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len()) } != 0 {
+        return None;
+    }
+    let length = buffer.iter().position(|byte| *byte == 0)?;
+    Some(buffer[..length].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_single_client_processes_are_signalled() {
+        assert!(is_client_pid(1234));
+        assert!(is_client_pid(i32::MAX as u32));
+        assert!(!is_client_pid(0));
+        assert!(!is_client_pid(1));
+        assert!(!is_client_pid(u32::MAX));
+        assert!(!is_client_pid(1 << 31));
+        assert!(!is_client_pid(std::process::id()));
+    }
+}
@@ -0,0 +1,224 @@
+use std::str::FromStr;
+
+use xcb::x;
+
+use crate::atoms::Atoms;
+
+use super::{read_property, WindowGeometry};
+
+/// `_NET_WM_DESKTOP` value of windows that are shown on every desktop.
+pub const ALL_DESKTOPS: u32 = 0xFFFFFFFF;
+
+/// States of `_NET_WM_STATE` that the window manager acts on.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NetWmState {
+    Fullscreen,
+    Above,
+    Below,
+    Sticky,
+    Hidden,
+    DemandsAttention,
+}
+
+impl NetWmState {
+    pub const ALL: [NetWmState; 6] = [
+        NetWmState::Fullscreen,
+        NetWmState::Above,
+        NetWmState::Below,
+        NetWmState::Sticky,
+        NetWmState::Hidden,
+        NetWmState::DemandsAttention,
+    ];
+
+    pub fn atom(&self, atoms: &Atoms) -> x::Atom {
+        match self {
+            NetWmState::Fullscreen => atoms.net_wm_state_fullscreen,
+            NetWmState::Above => atoms.net_wm_state_above,
+            NetWmState::Below => atoms.net_wm_state_below,
+            NetWmState::Sticky => atoms.net_wm_state_sticky,
+            NetWmState::Hidden => atoms.net_wm_state_hidden,
+            NetWmState::DemandsAttention => atoms.net_wm_state_demands_attention,
+        }
+    }
+
+    pub fn from_atom(atom: x::Atom, atoms: &Atoms) -> Option<NetWmState> {
+        NetWmState::ALL
+            .into_iter()
+            .find(|state| state.atom(atoms) == atom)
+    }
+}
+
+impl FromStr for NetWmState {
+    type Err = String;
+
+    fn from_str(state: &str) -> Result<Self, Self::Err> {
+        Ok(match state {
+            "fullscreen" => NetWmState::Fullscreen,
+            "above" => NetWmState::Above,
+            "below" => NetWmState::Below,
+            "sticky" => NetWmState::Sticky,
+            "hidden" => NetWmState::Hidden,
+            "demands-attention" => NetWmState::DemandsAttention,
+            _ => return Err(format!("unknown window state `{}`", state)),
+        })
+    }
+}
+
+/// `_NET_WM_WINDOW_TYPE`, windows without it are `Normal`, or `Dialog` if
+/// they are transient.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WindowType {
+    Desktop,
+    Dock,
+    Toolbar,
+    Menu,
+    Utility,
+    Splash,
+    Dialog,
+    Notification,
+    Normal,
+}
+
+impl WindowType {
+    const ALL: [WindowType; 9] = [
+        WindowType::Desktop,
+        WindowType::Dock,
+        WindowType::Toolbar,
+        WindowType::Menu,
+        WindowType::Utility,
+        WindowType::Splash,
+        WindowType::Dialog,
+        WindowType::Notification,
+        WindowType::Normal,
+    ];
+
+    pub fn atom(&self, atoms: &Atoms) -> x::Atom {
+        match self {
+            WindowType::Desktop => atoms.net_wm_window_type_desktop,
+            WindowType::Dock => atoms.net_wm_window_type_dock,
+            WindowType::Toolbar => atoms.net_wm_window_type_toolbar,
+            WindowType::Menu => atoms.net_wm_window_type_menu,
+            WindowType::Utility => atoms.net_wm_window_type_utility,
+            WindowType::Splash => atoms.net_wm_window_type_splash,
+            WindowType::Dialog => atoms.net_wm_window_type_dialog,
+            WindowType::Notification => atoms.net_wm_window_type_notification,
+            WindowType::Normal => atoms.net_wm_window_type_normal,
+        }
+    }
+
+    pub fn atoms(atoms: &Atoms) -> [x::Atom; 9] {
+        WindowType::ALL.map(|window_type| window_type.atom(atoms))
+    }
+
+    /// The first type of the list that is known, as the list is ordered by
+    /// preference.
+    pub fn read(connection: &xcb::Connection, atoms: &Atoms, window: x::Window) -> WindowType {
+        let types = read_property::<x::Atom>(
+            connection,
+            window,
+            atoms.net_wm_window_type,
+            x::ATOM_ATOM,
+            32,
+        );
+        if let Some(window_type) = types.unwrap_or_default().into_iter().find_map(|atom| {
+            WindowType::ALL
+                .into_iter()
+                .find(|window_type| window_type.atom(atoms) == atom)
+        }) {
+            return window_type;
+        }
+        match read_property::<x::Window>(
+            connection,
+            window,
+            x::ATOM_WM_TRANSIENT_FOR,
+            x::ATOM_WINDOW,
+            1,
+        ) {
+            Some(parent) if !parent.is_empty() => WindowType::Dialog,
+            _ => WindowType::Normal,
+        }
+    }
+
+    /// Docks and desktops are mapped, but never managed.
+    pub fn is_managed(&self) -> bool {
+        !matches!(self, WindowType::Desktop | WindowType::Dock)
+    }
+
+    /// Windows of these types are never tiled.
+    pub fn is_floating(&self) -> bool {
+        !matches!(self, WindowType::Normal)
+    }
+}
+
+/// Space reserved by a dock at the edges of the screen.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Strut {
+    left: u16,
+    right: u16,
+    top: u16,
+    bottom: u16,
+}
+
+impl Strut {
+    pub fn new(left: u16, right: u16, top: u16, bottom: u16) -> Strut {
+        Strut {
+            left,
+            right,
+            top,
+            bottom,
+        }
+    }
+
+    /// `_NET_WM_STRUT_PARTIAL`, or `_NET_WM_STRUT` for older clients. Only the
+    /// widths of the edges are used.
+    pub fn read(connection: &xcb::Connection, atoms: &Atoms, window: x::Window) -> Strut {
+        let value = read_property::<u32>(
+            connection,
+            window,
+            atoms.net_wm_strut_partial,
+            x::ATOM_CARDINAL,
+            12,
+        )
+        .filter(|value| value.len() >= 4)
+        .or_else(|| {
+            read_property::<u32>(connection, window, atoms.net_wm_strut, x::ATOM_CARDINAL, 4)
+                .filter(|value| value.len() >= 4)
+        });
+        match value {
+            Some(value) => Strut::new(
+                value[0].min(u16::MAX as u32) as u16,
+                value[1].min(u16::MAX as u32) as u16,
+                value[2].min(u16::MAX as u32) as u16,
+                value[3].min(u16::MAX as u32) as u16,
+            ),
+            None => Strut::default(),
+        }
+    }
+
+    /// Largest reservation of every edge.
+    pub fn union(&self, other: &Strut) -> Strut {
+        Strut::new(
+            self.left.max(other.left),
+            self.right.max(other.right),
+            self.top.max(other.top),
+            self.bottom.max(other.bottom),
+        )
+    }
+
+    /// Part of `area` that isn't covered by the strut, the strut is measured
+    /// from the edges of `screen`.
+    pub fn shrink(&self, area: WindowGeometry, screen: WindowGeometry) -> WindowGeometry {
+        let left = area.x().max(screen.x().saturating_add(self.left as i16));
+        let top = area.y().max(screen.y().saturating_add(self.top as i16));
+        let right = (area.x() as i32 + area.width() as i32)
+            .min(screen.x() as i32 + screen.width() as i32 - self.right as i32);
+        let bottom = (area.y() as i32 + area.height() as i32)
+            .min(screen.y() as i32 + screen.height() as i32 - self.bottom as i32);
+        WindowGeometry::new(
+            left,
+            top,
+            (right - left as i32).max(1) as u16,
+            (bottom - top as i32).max(1) as u16,
+        )
+    }
+}
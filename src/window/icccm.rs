@@ -0,0 +1,433 @@
+const INPUT_HINT: u32 = 1;
+const STATE_HINT: u32 = 1 << 1;
+const URGENCY_HINT: u32 = 1 << 8;
+
+const P_MIN_SIZE: u32 = 1 << 4;
+const P_MAX_SIZE: u32 = 1 << 5;
+const P_RESIZE_INC: u32 = 1 << 6;
+const P_ASPECT: u32 = 1 << 7;
+const P_BASE_SIZE: u32 = 1 << 8;
+/// Larger terms of an aspect ratio make no sense for 16 bit window sizes.
+const MAX_ASPECT_TERM: u32 = u16::MAX as u32;
+
+#[derive(Clone, Default)]
+pub struct WmClass {
+    instance: String,
+    class: String,
+}
+
+impl WmClass {
+    /// Parses the two null terminated strings of `WM_CLASS`.
+    pub fn parse(value: &[u8]) -> WmClass {
+        let mut parts = value
+            .split(|byte| *byte == 0)
+            .map(|part| String::from_utf8_lossy(part).into_owned());
+        WmClass {
+            instance: parts.next().unwrap_or_default(),
+            class: parts.next().unwrap_or_default(),
+        }
+    }
+
+    pub fn instance(&self) -> &str {
+        &self.instance
+    }
+
+    pub fn class(&self) -> &str {
+        &self.class
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum InitialState {
+    Withdrawn,
+    Normal,
+    Iconic,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct WmHints {
+    input: Option<bool>,
+    initial_state: Option<InitialState>,
+    urgent: bool,
+}
+
+impl WmHints {
+    pub fn parse(value: &[u32]) -> WmHints {
+        let flags = value.first().copied().unwrap_or_default();
+        let field = |index: usize, flag: u32| match flags & flag {
+            0 => None,
+            _ => value.get(index).copied(),
+        };
+        WmHints {
+            input: field(1, INPUT_HINT).map(|input| input != 0),
+            initial_state: field(2, STATE_HINT).and_then(|state| match state {
+                0 => Some(InitialState::Withdrawn),
+                1 => Some(InitialState::Normal),
+                3 => Some(InitialState::Iconic),
+                _ => None,
+            }),
+            urgent: flags & URGENCY_HINT != 0,
+        }
+    }
+
+    /// `None` if the client didn't set the input hint.
+    pub fn input(&self) -> Option<bool> {
+        self.input
+    }
+
+    pub fn initial_state(&self) -> Option<InitialState> {
+        self.initial_state
+    }
+
+    pub fn urgent(&self) -> bool {
+        self.urgent
+    }
+}
+
+/// ICCCM 4.1.7 input models, from the input hint and `WM_TAKE_FOCUS`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputModel {
+    NoInput,
+    Passive,
+    LocallyActive,
+    GloballyActive,
+}
+
+impl InputModel {
+    /// Clients without the input hint are treated as if they set it.
+    pub fn new(input: Option<bool>, takes_focus: bool) -> InputModel {
+        match (input.unwrap_or(true), takes_focus) {
+            (false, false) => InputModel::NoInput,
+            (true, false) => InputModel::Passive,
+            (true, true) => InputModel::LocallyActive,
+            (false, true) => InputModel::GloballyActive,
+        }
+    }
+
+    /// The window manager sets the focus itself, otherwise it only sends
+    /// `WM_TAKE_FOCUS`.
+    pub fn sets_focus(&self) -> bool {
+        matches!(self, InputModel::Passive | InputModel::LocallyActive)
+    }
+
+    pub fn takes_focus(&self) -> bool {
+        matches!(self, InputModel::LocallyActive | InputModel::GloballyActive)
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct SizeHints {
+    min_size: Option<(u32, u32)>,
+    max_size: Option<(u32, u32)>,
+    increment: Option<(u32, u32)>,
+    min_aspect: Option<(u32, u32)>,
+    max_aspect: Option<(u32, u32)>,
+    base_size: Option<(u32, u32)>,
+}
+
+impl SizeHints {
+    /// Parses `WM_NORMAL_HINTS`, fields with zero values are ignored.
+    pub fn parse(value: &[u32]) -> SizeHints {
+        let flags = value.first().copied().unwrap_or_default();
+        let pair = |index: usize, flag: u32| {
+            if flags & flag == 0 {
+                return None;
+            }
+            match (value.get(index), value.get(index + 1)) {
+                (Some(first), Some(second)) if *first > 0 || *second > 0 => Some((*first, *second)),
+                _ => None,
+            }
+        };
+        SizeHints {
+            min_size: pair(5, P_MIN_SIZE),
+            max_size: pair(7, P_MAX_SIZE),
+            increment: pair(9, P_RESIZE_INC),
+            min_aspect: pair(11, P_ASPECT).filter(is_sane_aspect),
+            max_aspect: pair(13, P_ASPECT).filter(is_sane_aspect),
+            base_size: pair(15, P_BASE_SIZE),
+        }
+    }
+
+    pub fn min_size(&self) -> Option<(u32, u32)> {
+        self.min_size.or(self.base_size)
+    }
+
+    pub fn max_size(&self) -> Option<(u32, u32)> {
+        self.max_size
+    }
+
+    pub fn increment(&self) -> Option<(u32, u32)> {
+        self.increment
+    }
+
+    pub fn base_size(&self) -> Option<(u32, u32)> {
+        self.base_size.or(self.min_size)
+    }
+
+    /// Window with equal min and max size can't be resized.
+    pub fn is_fixed(&self) -> bool {
+        self.max_size.is_some() && self.max_size == self.min_size()
+    }
+
+    /// Closest size to the requested one that satisfies the hints, as
+    /// described in ICCCM 4.1.2.3: the min size applies first, then the
+    /// aspect ratio and the increments, which keep the size on the grid of
+    /// the base size. The max size wins over everything.
+    pub fn constrain(&self, width: u32, height: u32) -> (u32, u32) {
+        let (base_width, base_height) = self.base_size().unwrap_or((0, 0));
+        let (min_width, min_height) = self.min_size().unwrap_or((1, 1));
+        let (max_width, max_height) = self.max_size.unwrap_or((0, 0));
+        let max_width = Some(max_width).filter(|max| *max > 0).unwrap_or(u32::MAX);
+        let max_height = Some(max_height).filter(|max| *max > 0).unwrap_or(u32::MAX);
+        let mut width = width.max(min_width).min(max_width);
+        let mut height = height.max(min_height).min(max_height);
+
+        if let (Some(min_aspect), Some(max_aspect)) = (self.min_aspect, self.max_aspect) {
+            // The ratios are compared crosswise, in u64 nothing overflows.
+            let aspect_width = width.saturating_sub(base_width) as u64;
+            let aspect_height = height.saturating_sub(base_height) as u64;
+            let (min_numerator, min_denominator) = (min_aspect.0 as u64, min_aspect.1 as u64);
+            let (max_numerator, max_denominator) = (max_aspect.0 as u64, max_aspect.1 as u64);
+            if aspect_height > 0 && aspect_width * max_denominator > aspect_height * max_numerator {
+                let aspect_width =
+                    (aspect_height * max_numerator + max_denominator / 2) / max_denominator;
+                width = base_width.saturating_add(aspect_width.min(u32::MAX as u64) as u32);
+            } else if aspect_height > 0
+                && aspect_width * min_denominator < aspect_height * min_numerator
+            {
+                let aspect_height =
+                    (aspect_width * min_denominator + min_numerator / 2) / min_numerator;
+                height = base_height.saturating_add(aspect_height.min(u32::MAX as u64) as u32);
+            }
+        }
+
+        let (width_increment, height_increment) = self.increment.unwrap_or((1, 1));
+        (
+            on_grid(width, base_width, width_increment, min_width, max_width),
+            on_grid(
+                height,
+                base_height,
+                height_increment,
+                min_height,
+                max_height,
+            ),
+        )
+    }
+}
+
+fn is_sane_aspect(aspect: &(u32, u32)) -> bool {
+    (1..=MAX_ASPECT_TERM).contains(&aspect.0) && (1..=MAX_ASPECT_TERM).contains(&aspect.1)
+}
+
+/// Largest length of the form `base + n * increment` up to `length`, or the
+/// next one if that's below `min`. Falls back to `max` if no length of the
+/// grid lies between `min` and `max`.
+fn on_grid(length: u32, base: u32, increment: u32, min: u32, max: u32) -> u32 {
+    let increment = increment.max(1);
+    let floor = |length: u32| match length < base {
+        true => length,
+        false => length - (length - base) % increment,
+    };
+    let mut length = floor(length);
+    if length < min {
+        length = floor(min);
+        if length < min {
+            length = length.saturating_add(increment);
+        }
+    }
+    if length > max {
+        length = match floor(max) {
+            floor if floor >= min => floor,
+            _ => max,
+        };
+    }
+    length.max(1)
+}
+
+/// Encodings of text properties like `WM_NAME`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextEncoding {
+    /// `STRING`
+    Latin1,
+    Utf8,
+    CompoundText,
+}
+
+const ESCAPE: u8 = 0x1b;
+const CONTROL_SEQUENCE: u8 = 0x9b;
+
+/// Decodes a text property. `COMPOUND_TEXT` starts as Latin-1 and may embed
+/// UTF-8 between `ESC % G` and `ESC % @`, which is how Xlib writes text that
+/// has no other character set. Characters of the other sets, e.g. the CJK
+/// ones, become `?`.
+pub fn decode_text(value: &[u8], encoding: TextEncoding) -> String {
+    match encoding {
+        TextEncoding::Latin1 => value.iter().map(|byte| *byte as char).collect(),
+        TextEncoding::Utf8 => String::from_utf8_lossy(value).into_owned(),
+        TextEncoding::CompoundText => decode_compound_text(value),
+    }
+}
+
+fn decode_compound_text(value: &[u8]) -> String {
+    let mut text = String::new();
+    // Bytes per character of the sets in GL and GR, 0 while they hold ASCII
+    // and the right half of Latin-1.
+    let (mut left, mut right) = (0, 0);
+    let mut partial = 0;
+    let mut index = 0;
+    while index < value.len() {
+        let byte = value[index];
+        index += 1;
+        match byte {
+            ESCAPE => {
+                let rest = &value[index..];
+                let Some(end) = rest.iter().position(|byte| (0x30..=0x7e).contains(byte)) else {
+                    break;
+                };
+                index += end + 1;
+                partial = 0;
+                match &rest[..=end] {
+                    b"%G" => {
+                        let rest = &value[index..];
+                        let end = rest
+                            .windows(3)
+                            .position(|sequence| sequence == b"\x1b%@")
+                            .unwrap_or(rest.len());
+                        text.push_str(&String::from_utf8_lossy(&rest[..end]));
+                        index += (end + 3).min(rest.len());
+                    }
+                    b"(B" => left = 0,
+                    b"-A" => right = 0,
+                    [b'(', _] => left = 1,
+                    [b')' | b'-', _] => right = 1,
+                    [b'$', b'(', _] | [b'$', _] => left = 2,
+                    [b'$', b')', _] => right = 2,
+                    _ => {}
+                }
+            }
+            CONTROL_SEQUENCE => {
+                while index < value.len() && !(0x40..=0x7e).contains(&value[index]) {
+                    index += 1;
+                }
+                index += 1;
+            }
+            b'\t' | b'\n' | b' ' => text.push(byte as char),
+            0x20..=0x7f if left == 0 => text.push(byte as char),
+            0xa0..=0xff if right == 0 => text.push(byte as char),
+            0x21..=0x7e | 0xa1..=0xfe => {
+                let width = match byte < 0x80 {
+                    true => left,
+                    false => right,
+                };
+                partial += 1;
+                if partial == width {
+                    text.push('?');
+                    partial = 0;
+                }
+            }
+            _ => {}
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `WM_NORMAL_HINTS` with the given min, max, increment and base size.
+    fn size_hints(
+        min: (u32, u32),
+        max: (u32, u32),
+        increment: (u32, u32),
+        base: (u32, u32),
+    ) -> SizeHints {
+        let mut value = [0; 18];
+        value[0] = P_MIN_SIZE | P_MAX_SIZE | P_RESIZE_INC | P_BASE_SIZE;
+        [value[5], value[6]] = [min.0, min.1];
+        [value[7], value[8]] = [max.0, max.1];
+        [value[9], value[10]] = [increment.0, increment.1];
+        [value[15], value[16]] = [base.0, base.1];
+        SizeHints::parse(&value)
+    }
+
+    #[test]
+    fn increments_round_down_to_the_base_size_grid() {
+        let hints = size_hints((0, 0), (0, 0), (10, 20), (4, 6));
+        assert_eq!(hints.constrain(100, 100), (94, 86));
+        assert_eq!(hints.constrain(104, 106), (104, 106));
+    }
+
+    #[test]
+    fn min_size_stays_on_the_increment_grid() {
+        let hints = size_hints((25, 25), (0, 0), (10, 10), (4, 4));
+        assert_eq!(hints.constrain(10, 10), (34, 34));
+        assert_eq!(hints.constrain(30, 40), (34, 34));
+    }
+
+    #[test]
+    fn max_size_wins() {
+        let hints = size_hints((20, 20), (58, 50), (10, 10), (4, 4));
+        assert_eq!(hints.constrain(500, 500), (54, 44));
+        // No size of the grid fits between min and max.
+        let hints = size_hints((20, 20), (23, 23), (10, 10), (4, 4));
+        assert_eq!(hints.constrain(500, 500), (23, 23));
+    }
+
+    #[test]
+    fn aspect_ratio_is_kept() {
+        let mut value = [0; 18];
+        value[0] = P_ASPECT;
+        [value[11], value[12], value[13], value[14]] = [1, 1, 2, 1];
+        let hints = SizeHints::parse(&value);
+        assert_eq!(hints.constrain(300, 100), (200, 100));
+        assert_eq!(hints.constrain(100, 300), (100, 100));
+        assert_eq!(hints.constrain(150, 100), (150, 100));
+    }
+
+    #[test]
+    fn hostile_hints_are_survived() {
+        let mut value = [u32::MAX; 18];
+        value[0] = P_MIN_SIZE | P_MAX_SIZE | P_RESIZE_INC | P_ASPECT | P_BASE_SIZE;
+        let hints = SizeHints::parse(&value);
+        assert_eq!(hints.min_aspect, None);
+        hints.constrain(100, 100);
+        // Zero numerators would divide by zero.
+        [value[11], value[12], value[13], value[14]] = [0, 1, 0, 1];
+        assert_eq!(SizeHints::parse(&value).max_aspect, None);
+        let hints = size_hints((u32::MAX, u32::MAX), (0, 0), (u32::MAX, 1), (7, 7));
+        hints.constrain(100, 100);
+    }
+
+    #[test]
+    fn no_hints_keep_the_size() {
+        assert_eq!(SizeHints::default().constrain(123, 45), (123, 45));
+        assert_eq!(SizeHints::default().constrain(0, 0), (1, 1));
+    }
+
+    #[test]
+    fn latin1_and_utf8_are_decoded() {
+        assert_eq!(decode_text(b"caf\xe9", TextEncoding::Latin1), "café");
+        assert_eq!(decode_text("café".as_bytes(), TextEncoding::Utf8), "café");
+    }
+
+    #[test]
+    fn compound_text_is_decoded() {
+        assert_eq!(decode_text(b"caf\xe9", TextEncoding::CompoundText), "café");
+        let mut embedded = b"a \x1b%G".to_vec();
+        embedded.extend_from_slice("日本".as_bytes());
+        embedded.extend_from_slice(b"\x1b%@ b");
+        assert_eq!(
+            decode_text(&embedded, TextEncoding::CompoundText),
+            "a 日本 b"
+        );
+        // JIS X 0208 in GL, then back to ASCII.
+        assert_eq!(
+            decode_text(
+                b"x\x1b$(B\x46\x7c\x4b\x5c\x1b(By",
+                TextEncoding::CompoundText
+            ),
+            "x??y"
+        );
+    }
+}
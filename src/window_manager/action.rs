@@ -0,0 +1,244 @@
+use std::str::FromStr;
+
+use xcb::{x, XidNew};
+
+use super::{compositor::LayoutMessage, direction::Direction};
+use crate::{config::LayoutKind, window::ewmh::NetWmState};
+
+/// Command that can be sent to the window manager over IPC. Window commands
+/// take an optional window id, decimal or hexadecimal with `0x`.
+pub enum Action {
+    Quit,
+    Restart,
+    View(u32),
+    Close(Option<x::Window>),
+    Terminate(Option<x::Window>),
+    Kill(Option<x::Window>),
+    ToggleState(NetWmState, Option<x::Window>),
+    /// Takes the window out of the layout, or puts it back.
+    ToggleFloating(Option<x::Window>),
+    /// Focuses the window that was focused before the current one.
+    FocusLast,
+    /// Steps through the focus history while a modifier is held.
+    CycleFocus(Cycle),
+    /// Focuses the tiled window next to the focused one.
+    Focus(Direction),
+    /// Swaps the focused window with the tiled window next to it.
+    Swap(Direction),
+    Layout(LayoutMessage),
+    SetLayout(LayoutKind),
+    /// Switches the current tag to the next or previous layout.
+    CycleLayout(Cycle),
+    /// Focuses the next or previous tiled window in layout order.
+    FocusStep(Cycle),
+    FocusMonitor(MonitorTarget),
+    /// Moves the window to the tag shown on the monitor.
+    SendToMonitor(MonitorTarget, Option<x::Window>),
+    /// Exchanges the tags shown on the focused monitor and the other one.
+    SwapMonitors(MonitorTarget),
+    /// Shows the tag of the focused monitor on the other one, with its
+    /// windows, and focuses it.
+    MoveTagToMonitor(MonitorTarget),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Cycle {
+    Next,
+    Previous,
+}
+
+/// Monitor next to the focused one in server order, or a monitor by its
+/// output name, like `HDMI-1`.
+pub enum MonitorTarget {
+    Step(Cycle),
+    Name(String),
+}
+
+impl FromStr for MonitorTarget {
+    type Err = String;
+
+    fn from_str(target: &str) -> Result<Self, Self::Err> {
+        Ok(match target {
+            "next" => MonitorTarget::Step(Cycle::Next),
+            "prev" => MonitorTarget::Step(Cycle::Previous),
+            name => MonitorTarget::Name(name.to_string()),
+        })
+    }
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(command: &str) -> Result<Self, Self::Err> {
+        let mut words = command.split_whitespace();
+        let action = match words.next().unwrap_or_default() {
+            "quit" => Action::Quit,
+            "restart" => Action::Restart,
+            "view" => Action::View(
+                words
+                    .next()
+                    .and_then(|tag| tag.parse().ok())
+                    .ok_or("`view` needs a tag number")?,
+            ),
+            "close" => Action::Close(window_argument(words.next())?),
+            "terminate" => Action::Terminate(window_argument(words.next())?),
+            "kill" => Action::Kill(window_argument(words.next())?),
+            "toggle-state" => Action::ToggleState(
+                words
+                    .next()
+                    .ok_or("`toggle-state` needs a state")?
+                    .parse()?,
+                window_argument(words.next())?,
+            ),
+            "toggle-floating" => Action::ToggleFloating(window_argument(words.next())?),
+            "focus-last" => Action::FocusLast,
+            "cycle-focus" => Action::CycleFocus(match words.next() {
+                Some("next") | None => Cycle::Next,
+                Some("prev") => Cycle::Previous,
+                Some(word) => return Err(format!("unknown cycle direction `{}`", word)),
+            }),
+            "inc-master" => Action::Layout(LayoutMessage::ChangeMasterCount(number_argument(
+                words.next(),
+                1,
+            )?)),
+            "dec-master" => Action::Layout(LayoutMessage::ChangeMasterCount(negated_argument(
+                words.next(),
+                1,
+            )?)),
+            "set-master" => Action::Layout(LayoutMessage::SetMasterCount(
+                words
+                    .next()
+                    .ok_or("`set-master` needs a count")?
+                    .parse()
+                    .map_err(|_| "invalid master count")?,
+            )),
+            "grow-master" => Action::Layout(LayoutMessage::ChangeMasterRatio(number_argument(
+                words.next(),
+                0.05,
+            )?)),
+            "shrink-master" => Action::Layout(LayoutMessage::ChangeMasterRatio(-number_argument(
+                words.next(),
+                0.05,
+            )?)),
+            "set-master-ratio" => Action::Layout(LayoutMessage::SetMasterRatio(
+                words
+                    .next()
+                    .ok_or("`set-master-ratio` needs a ratio")?
+                    .parse()
+                    .map_err(|_| "invalid master ratio")?,
+            )),
+            "master-position" => Action::Layout(LayoutMessage::SetMasterPosition(
+                words
+                    .next()
+                    .ok_or("`master-position` needs a position")?
+                    .parse()?,
+            )),
+            "set-layout" => {
+                Action::SetLayout(words.next().ok_or("`set-layout` needs a layout")?.parse()?)
+            }
+            "cycle-layout" => Action::CycleLayout(match words.next() {
+                Some("next") | None => Cycle::Next,
+                Some("prev") => Cycle::Previous,
+                Some(word) => return Err(format!("unknown cycle direction `{}`", word)),
+            }),
+            "inc-gaps" => {
+                Action::Layout(LayoutMessage::ChangeGaps(number_argument(words.next(), 1)?))
+            }
+            "dec-gaps" => Action::Layout(LayoutMessage::ChangeGaps(negated_argument(
+                words.next(),
+                1,
+            )?)),
+            "presel" => match words.next() {
+                Some("cancel") => Action::Layout(LayoutMessage::CancelPreselection),
+                Some(direction) => Action::Layout(LayoutMessage::Preselect(
+                    direction.parse()?,
+                    number_argument(words.next(), 0.5)?,
+                )),
+                None => return Err("`presel` needs a direction".to_string()),
+            },
+            "rotate" => Action::Layout(LayoutMessage::Rotate(match words.next() {
+                Some("clockwise") | None => true,
+                Some("counterclockwise") => false,
+                Some(word) => return Err(format!("unknown rotation `{}`", word)),
+            })),
+            "flip" => Action::Layout(LayoutMessage::Flip(
+                words.next().ok_or("`flip` needs an orientation")?.parse()?,
+            )),
+            "balance" => Action::Layout(LayoutMessage::Balance),
+            "layout" => Action::Layout(match words.next() {
+                Some("toggle-split") => LayoutMessage::ToggleSplit,
+                Some(layout) => LayoutMessage::SetContainerLayout(layout.parse()?),
+                None => return Err("`layout` needs a container layout".to_string()),
+            }),
+            "split" => Action::Layout(LayoutMessage::Split(
+                words
+                    .next()
+                    .ok_or("`split` needs an orientation")?
+                    .parse()?,
+            )),
+            "move" => Action::Layout(LayoutMessage::Move(
+                words.next().ok_or("`move` needs a direction")?.parse()?,
+            )),
+            "focus-parent" => Action::Layout(LayoutMessage::FocusParent),
+            "focus-child" => Action::Layout(LayoutMessage::FocusChild),
+            "focus-monitor" => Action::FocusMonitor(words.next().unwrap_or("next").parse()?),
+            "send-to-monitor" => Action::SendToMonitor(
+                words
+                    .next()
+                    .ok_or("`send-to-monitor` needs a monitor")?
+                    .parse()?,
+                window_argument(words.next())?,
+            ),
+            "swap-monitors" => Action::SwapMonitors(words.next().unwrap_or("next").parse()?),
+            "move-tag-to" => Action::MoveTagToMonitor(
+                words
+                    .next()
+                    .ok_or("`move-tag-to` needs a monitor")?
+                    .parse()?,
+            ),
+            "focus-next" => Action::FocusStep(Cycle::Next),
+            "focus-prev" => Action::FocusStep(Cycle::Previous),
+            command if command.starts_with("focus-") => {
+                Action::Focus(command.trim_start_matches("focus-").parse()?)
+            }
+            command if command.starts_with("swap-") => {
+                Action::Swap(command.trim_start_matches("swap-").parse()?)
+            }
+            command if command.starts_with("resize-") => Action::Layout(LayoutMessage::Resize(
+                command.trim_start_matches("resize-").parse()?,
+                number_argument(words.next(), 0.05)?,
+            )),
+            _ => return Err(format!("unknown command `{}`", command)),
+        };
+        Ok(action)
+    }
+}
+
+fn number_argument<N: FromStr>(word: Option<&str>, default: N) -> Result<N, String> {
+    match word {
+        Some(word) => word
+            .parse()
+            .map_err(|_| format!("invalid number `{}`", word)),
+        None => Ok(default),
+    }
+}
+
+/// Step of a `dec-` action, `i32::MIN` has no negation.
+fn negated_argument(word: Option<&str>, default: i32) -> Result<i32, String> {
+    let number: i32 = number_argument(word, default)?;
+    number
+        .checked_neg()
+        .ok_or_else(|| format!("invalid number `{}`", number))
+}
+
+fn window_argument(word: Option<&str>) -> Result<Option<x::Window>, String> {
+    let Some(word) = word else {
+        return Ok(None);
+    };
+    let id = match word.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => word.parse(),
+    }
+    .map_err(|_| format!("invalid window id `{}`", word))?;
+    Ok(Some(x::Window::new(id)))
+}
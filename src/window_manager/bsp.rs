@@ -0,0 +1,766 @@
+//! bspwm style layout: every tag holds a binary tree, whose leaves are the
+//! windows. A new window splits the leaf of the focused window, in the
+//! preselected direction or along the longer side of the leaf.
+
+use serde::{Deserialize, Serialize};
+use xcb::{x, Xid};
+
+use super::{
+    compositor::{Client, Compositor, LayoutMessage, Placement},
+    direction::Direction,
+    tiling::{change_gap, place, shrink, tiled},
+};
+use crate::{config::TilingConfig, window::WindowGeometry};
+
+const MIN_SPLIT_RATIO: f32 = 0.05;
+const MAX_SPLIT_RATIO: f32 = 0.95;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Orientation {
+    /// The children are side by side.
+    Horizontal,
+    /// The children are one above the other.
+    Vertical,
+}
+
+impl std::str::FromStr for Orientation {
+    type Err = String;
+
+    fn from_str(orientation: &str) -> Result<Self, Self::Err> {
+        Ok(match orientation {
+            "horizontal" => Orientation::Horizontal,
+            "vertical" => Orientation::Vertical,
+            _ => return Err(format!("unknown orientation `{}`", orientation)),
+        })
+    }
+}
+
+/// Windows are stored by their ids, so the tree can be saved on restart.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Node {
+    Leaf(u32),
+    Split {
+        orientation: Orientation,
+        /// Part of the split taken by the first child.
+        ratio: f32,
+        first: Box<Node>,
+        second: Box<Node>,
+    },
+}
+
+impl Node {
+    pub fn contains(&self, window: u32) -> bool {
+        match self {
+            Node::Leaf(leaf) => *leaf == window,
+            Node::Split { first, second, .. } => first.contains(window) || second.contains(window),
+        }
+    }
+
+    pub fn windows(&self) -> Vec<u32> {
+        match self {
+            Node::Leaf(leaf) => vec![*leaf],
+            Node::Split { first, second, .. } => {
+                let mut windows = first.windows();
+                windows.extend(second.windows());
+                windows
+            }
+        }
+    }
+
+    /// A window is in one leaf at most.
+    fn has_unique_leaves(&self) -> bool {
+        let mut windows = self.windows();
+        let count = windows.len();
+        windows.sort_unstable();
+        windows.dedup();
+        windows.len() == count
+    }
+
+    /// The tree without the window, `None` if nothing is left. The sibling of
+    /// the window takes the place of their split.
+    pub fn remove(self, window: u32) -> Option<Node> {
+        match self {
+            Node::Leaf(leaf) if leaf == window => None,
+            Node::Leaf(_) => Some(self),
+            Node::Split {
+                orientation,
+                ratio,
+                first,
+                second,
+            } => match (first.remove(window), second.remove(window)) {
+                (Some(first), Some(second)) => Some(Node::Split {
+                    orientation,
+                    ratio,
+                    first: Box::new(first),
+                    second: Box::new(second),
+                }),
+                (Some(node), None) | (None, Some(node)) => Some(node),
+                (None, None) => None,
+            },
+        }
+    }
+
+    /// Splits the leaf of `target` between it and `window`, which becomes the
+    /// first child if `first`.
+    pub fn insert(
+        &mut self,
+        target: u32,
+        window: u32,
+        orientation: Orientation,
+        first: bool,
+        ratio: f32,
+    ) -> bool {
+        match self {
+            Node::Leaf(leaf) if *leaf == target => {
+                let old = Box::new(Node::Leaf(target));
+                let new = Box::new(Node::Leaf(window));
+                let (first, second) = match first {
+                    true => (new, old),
+                    false => (old, new),
+                };
+                *self = Node::Split {
+                    orientation,
+                    ratio: ratio.clamp(MIN_SPLIT_RATIO, MAX_SPLIT_RATIO),
+                    first,
+                    second,
+                };
+                true
+            }
+            Node::Leaf(_) => false,
+            Node::Split {
+                first: first_child,
+                second: second_child,
+                ..
+            } => {
+                first_child.insert(target, window, orientation, first, ratio)
+                    || second_child.insert(target, window, orientation, first, ratio)
+            }
+        }
+    }
+
+    /// Cells of the windows in the area, in tree order.
+    pub fn cells(&self, area: WindowGeometry, gap: u16, cells: &mut Vec<(u32, WindowGeometry)>) {
+        let Node::Split {
+            orientation,
+            ratio,
+            first,
+            second,
+        } = self
+        else {
+            if let Node::Leaf(leaf) = self {
+                cells.push((*leaf, area));
+            }
+            return;
+        };
+        let length = match orientation {
+            Orientation::Horizontal => area.width(),
+            Orientation::Vertical => area.height(),
+        }
+        .saturating_sub(gap);
+        let first_length = ((length as f32 * ratio).round() as u16).clamp(1, length.max(1));
+        let second_length = length.saturating_sub(first_length).max(1);
+        let (first_area, second_area) = match orientation {
+            Orientation::Horizontal => (
+                WindowGeometry::new(area.x(), area.y(), first_length, area.height()),
+                WindowGeometry::new(
+                    area.x() + (first_length + gap) as i16,
+                    area.y(),
+                    second_length,
+                    area.height(),
+                ),
+            ),
+            Orientation::Vertical => (
+                WindowGeometry::new(area.x(), area.y(), area.width(), first_length),
+                WindowGeometry::new(
+                    area.x(),
+                    area.y() + (first_length + gap) as i16,
+                    area.width(),
+                    second_length,
+                ),
+            ),
+        };
+        first.cells(first_area, gap, cells);
+        second.cells(second_area, gap, cells);
+    }
+
+    fn is_parent(&self, window: u32) -> bool {
+        match self {
+            Node::Leaf(_) => false,
+            Node::Split { first, second, .. } => {
+                **first == Node::Leaf(window) || **second == Node::Leaf(window)
+            }
+        }
+    }
+
+    /// Split whose child is the leaf of the window.
+    pub fn parent_mut(&mut self, window: u32) -> Option<&mut Node> {
+        if self.is_parent(window) {
+            return Some(self);
+        }
+        match self {
+            Node::Leaf(_) => None,
+            Node::Split { first, second, .. } => match first.contains(window) {
+                true => first.parent_mut(window),
+                false => second.parent_mut(window),
+            },
+        }
+    }
+
+    /// Turns the subtree by 90 degrees.
+    pub fn rotate(&mut self, clockwise: bool) {
+        if let Node::Split {
+            orientation,
+            ratio,
+            first,
+            second,
+        } = self
+        {
+            // Side by side children end up one above the other in the same
+            // order, the other way around the order changes.
+            let swap = (*orientation == Orientation::Vertical) == clockwise;
+            *orientation = match orientation {
+                Orientation::Horizontal => Orientation::Vertical,
+                Orientation::Vertical => Orientation::Horizontal,
+            };
+            if swap {
+                std::mem::swap(first, second);
+                *ratio = 1.0 - *ratio;
+            }
+            first.rotate(clockwise);
+            second.rotate(clockwise);
+        }
+    }
+
+    /// Mirrors the subtree, `Horizontal` swaps left and right.
+    pub fn flip(&mut self, flip: Orientation) {
+        if let Node::Split {
+            orientation,
+            ratio,
+            first,
+            second,
+        } = self
+        {
+            if *orientation == flip {
+                std::mem::swap(first, second);
+                *ratio = 1.0 - *ratio;
+            }
+            first.flip(flip);
+            second.flip(flip);
+        }
+    }
+
+    /// Gives every window the same share of the area.
+    pub fn balance(&mut self) {
+        if let Node::Split {
+            ratio,
+            first,
+            second,
+            ..
+        } = self
+        {
+            let first_count = first.windows().len();
+            let second_count = second.windows().len();
+            *ratio = first_count as f32 / (first_count + second_count) as f32;
+            first.balance();
+            second.balance();
+        }
+    }
+
+    /// Moves the edge of the window on the side of `direction` by `step`, the
+    /// closest split with such an edge changes.
+    pub fn resize(&mut self, window: u32, direction: Direction, step: f32) -> bool {
+        let Node::Split {
+            orientation,
+            ratio,
+            first,
+            second,
+        } = self
+        else {
+            return false;
+        };
+        if first.resize(window, direction, step) || second.resize(window, direction, step) {
+            return true;
+        }
+        let delta = match (direction, *orientation) {
+            (Direction::Right, Orientation::Horizontal)
+            | (Direction::Down, Orientation::Vertical)
+                if first.contains(window) =>
+            {
+                step
+            }
+            (Direction::Left, Orientation::Horizontal) | (Direction::Up, Orientation::Vertical)
+                if second.contains(window) =>
+            {
+                -step
+            }
+            _ => return false,
+        };
+        *ratio = (*ratio + delta).clamp(MIN_SPLIT_RATIO, MAX_SPLIT_RATIO);
+        true
+    }
+}
+
+/// Direction and ratio of the next split.
+#[derive(Clone, Copy)]
+struct Preselection {
+    direction: Direction,
+    ratio: f32,
+}
+
+/// Saved on restart.
+#[derive(Serialize, Deserialize)]
+struct BspState {
+    tree: Option<Node>,
+}
+
+pub struct BspCompositor {
+    tree: Option<Node>,
+    preselection: Option<Preselection>,
+    border_size: u16,
+    inner_gap: u16,
+    outer_gap: u16,
+    smart_borders: bool,
+    smart_gaps: bool,
+}
+
+impl BspCompositor {
+    pub fn new(tiling_config: TilingConfig, border_size: u16) -> BspCompositor {
+        let gaps_config = tiling_config.gaps_config();
+        BspCompositor {
+            tree: None,
+            preselection: None,
+            border_size,
+            inner_gap: gaps_config.as_ref().map_or(0, |gaps| gaps.inner_size()),
+            outer_gap: gaps_config.as_ref().map_or(0, |gaps| gaps.outer_size()),
+            smart_borders: tiling_config.smart_borders(),
+            smart_gaps: tiling_config.smart_gaps(),
+        }
+    }
+
+    /// Splits the leaf of the focused window, or of the last window if the
+    /// focused one isn't in the tree.
+    fn insert(&mut self, window: u32, focused: Option<u32>, area: WindowGeometry) {
+        let Some(tree) = &mut self.tree else {
+            self.tree = Some(Node::Leaf(window));
+            return;
+        };
+        let target = focused
+            .filter(|focused| tree.contains(*focused))
+            .or_else(|| tree.windows().last().copied())
+            .unwrap();
+        let (orientation, first, ratio) = match self.preselection.take() {
+            Some(preselection) => (
+                match preselection.direction {
+                    Direction::Left | Direction::Right => Orientation::Horizontal,
+                    Direction::Up | Direction::Down => Orientation::Vertical,
+                },
+                matches!(preselection.direction, Direction::Left | Direction::Up),
+                preselection.ratio,
+            ),
+            None => {
+                let mut cells = Vec::new();
+                tree.cells(area, self.inner_gap, &mut cells);
+                let cell = cells
+                    .iter()
+                    .find(|(leaf, _)| *leaf == target)
+                    .map_or(area, |(_, cell)| *cell);
+                let orientation = match cell.width() >= cell.height() {
+                    true => Orientation::Horizontal,
+                    false => Orientation::Vertical,
+                };
+                (orientation, false, 0.5)
+            }
+        };
+        tree.insert(target, window, orientation, first, ratio);
+    }
+
+    /// Subtree the focused window belongs to, the whole tree without focus.
+    fn focused_subtree(&mut self, focused: Option<x::Window>) -> Option<&mut Node> {
+        let tree = self.tree.as_mut()?;
+        match focused {
+            Some(focused) if tree.contains(focused.resource_id()) => {
+                tree.parent_mut(focused.resource_id())
+            }
+            _ => Some(tree),
+        }
+    }
+}
+
+impl Compositor for BspCompositor {
+    fn locate_windows(&mut self, area: WindowGeometry, clients: &[Client]) -> Vec<Placement> {
+        let clients = tiled(clients);
+        let windows: Vec<u32> = clients
+            .iter()
+            .map(|client| client.window().resource_id())
+            .collect();
+        if let Some(tree) = self.tree.take() {
+            self.tree = tree
+                .windows()
+                .into_iter()
+                .filter(|window| !windows.contains(window))
+                .try_fold(tree, |tree, window| tree.remove(window));
+        }
+        let area = match clients.len() == 1 && self.smart_gaps {
+            true => area,
+            false => shrink(area, self.outer_gap),
+        };
+        let focused = clients
+            .iter()
+            .find(|client| client.is_focused())
+            .map(|client| client.window().resource_id());
+        for window in &windows {
+            if !self
+                .tree
+                .as_ref()
+                .is_some_and(|tree| tree.contains(*window))
+            {
+                self.insert(*window, focused, area);
+            }
+        }
+        let mut cells = Vec::new();
+        if let Some(tree) = &self.tree {
+            tree.cells(area, self.inner_gap, &mut cells);
+        }
+        let cells = windows
+            .iter()
+            .map(|window| {
+                cells
+                    .iter()
+                    .find(|(leaf, _)| leaf == window)
+                    .map_or(area, |(_, cell)| *cell)
+            })
+            .collect();
+        place(&clients, cells, self.border_size, self.smart_borders)
+    }
+
+    fn name(&self, _focused: Option<usize>, _count: usize) -> String {
+        "[B]".to_string()
+    }
+
+    fn handle_message(&mut self, message: LayoutMessage, focused: Option<x::Window>) {
+        match message {
+            LayoutMessage::Preselect(direction, ratio) => {
+                self.preselection = Some(Preselection { direction, ratio })
+            }
+            LayoutMessage::CancelPreselection => self.preselection = None,
+            LayoutMessage::Rotate(clockwise) => {
+                if let Some(subtree) = self.focused_subtree(focused) {
+                    subtree.rotate(clockwise);
+                }
+            }
+            LayoutMessage::Flip(orientation) => {
+                if let Some(subtree) = self.focused_subtree(focused) {
+                    subtree.flip(orientation);
+                }
+            }
+            LayoutMessage::Balance => {
+                if let Some(tree) = &mut self.tree {
+                    tree.balance();
+                }
+            }
+            LayoutMessage::Resize(direction, step) => {
+                if let (Some(tree), Some(focused)) = (&mut self.tree, focused) {
+                    tree.resize(focused.resource_id(), direction, step);
+                }
+            }
+            LayoutMessage::ChangeGaps(delta) => {
+                self.inner_gap = change_gap(self.inner_gap, delta);
+                self.outer_gap = change_gap(self.outer_gap, delta);
+            }
+            _ => {}
+        }
+    }
+
+    fn save_state(&self) -> Option<String> {
+        toml::to_string(&BspState {
+            tree: self.tree.clone(),
+        })
+        .ok()
+    }
+
+    fn restore_state(&mut self, state: &str) {
+        if let Ok(state) = toml::from_str::<BspState>(state) {
+            if state.tree.as_ref().is_none_or(Node::has_unique_leaves) {
+                self.tree = state.tree;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_manager::compositor::tests::{clients, window};
+
+    fn split(orientation: Orientation, ratio: f32, first: Node, second: Node) -> Node {
+        Node::Split {
+            orientation,
+            ratio,
+            first: Box::new(first),
+            second: Box::new(second),
+        }
+    }
+
+    /// Window 1 on the left, windows 2 and 3 one above the other on the right.
+    fn tree() -> Node {
+        split(
+            Orientation::Horizontal,
+            0.5,
+            Node::Leaf(1),
+            split(Orientation::Vertical, 0.5, Node::Leaf(2), Node::Leaf(3)),
+        )
+    }
+
+    fn area() -> WindowGeometry {
+        WindowGeometry::new(0, 0, 1000, 500)
+    }
+
+    fn geometries(placements: &[Placement]) -> Vec<WindowGeometry> {
+        placements
+            .iter()
+            .map(|placement| placement.geometry())
+            .collect()
+    }
+
+    #[test]
+    fn insert_splits_the_leaf_of_the_target() {
+        let mut node = Node::Leaf(1);
+        assert!(node.insert(1, 2, Orientation::Horizontal, false, 0.5));
+        assert_eq!(
+            node,
+            split(Orientation::Horizontal, 0.5, Node::Leaf(1), Node::Leaf(2))
+        );
+        assert!(node.insert(2, 3, Orientation::Vertical, true, 2.0));
+        assert_eq!(
+            node,
+            split(
+                Orientation::Horizontal,
+                0.5,
+                Node::Leaf(1),
+                split(
+                    Orientation::Vertical,
+                    MAX_SPLIT_RATIO,
+                    Node::Leaf(3),
+                    Node::Leaf(2)
+                ),
+            )
+        );
+        assert!(!node.insert(4, 5, Orientation::Vertical, false, 0.5));
+    }
+
+    #[test]
+    fn remove_gives_the_place_to_the_sibling() {
+        assert_eq!(
+            tree().remove(2),
+            Some(split(
+                Orientation::Horizontal,
+                0.5,
+                Node::Leaf(1),
+                Node::Leaf(3)
+            ))
+        );
+        assert_eq!(
+            tree().remove(1),
+            Some(split(
+                Orientation::Vertical,
+                0.5,
+                Node::Leaf(2),
+                Node::Leaf(3)
+            ))
+        );
+        assert_eq!(tree().remove(4), Some(tree()));
+        assert_eq!(Node::Leaf(1).remove(1), None);
+    }
+
+    #[test]
+    fn rotate_turns_the_tree() {
+        let mut node = split(Orientation::Horizontal, 0.25, Node::Leaf(1), Node::Leaf(2));
+        node.rotate(true);
+        assert_eq!(
+            node,
+            split(Orientation::Vertical, 0.25, Node::Leaf(1), Node::Leaf(2))
+        );
+        node.rotate(true);
+        assert_eq!(
+            node,
+            split(Orientation::Horizontal, 0.75, Node::Leaf(2), Node::Leaf(1))
+        );
+        node.rotate(false);
+        assert_eq!(
+            node,
+            split(Orientation::Vertical, 0.25, Node::Leaf(1), Node::Leaf(2))
+        );
+    }
+
+    #[test]
+    fn flip_mirrors_the_splits_of_the_orientation() {
+        let mut node = tree();
+        node.flip(Orientation::Horizontal);
+        assert_eq!(
+            node,
+            split(
+                Orientation::Horizontal,
+                0.5,
+                split(Orientation::Vertical, 0.5, Node::Leaf(2), Node::Leaf(3)),
+                Node::Leaf(1),
+            )
+        );
+        let mut node = tree();
+        node.flip(Orientation::Vertical);
+        assert_eq!(
+            node,
+            split(
+                Orientation::Horizontal,
+                0.5,
+                Node::Leaf(1),
+                split(Orientation::Vertical, 0.5, Node::Leaf(3), Node::Leaf(2)),
+            )
+        );
+    }
+
+    #[test]
+    fn balance_gives_every_window_the_same_share() {
+        let mut node = tree();
+        node.balance();
+        let Node::Split { ratio, .. } = node else {
+            panic!("the root is a split");
+        };
+        assert_eq!(ratio, 1.0 / 3.0);
+    }
+
+    #[test]
+    fn resize_moves_the_closest_edge() {
+        let mut node = tree();
+        assert!(node.resize(3, Direction::Up, 0.25));
+        assert!(node.resize(3, Direction::Left, 0.25));
+        assert_eq!(
+            node,
+            split(
+                Orientation::Horizontal,
+                0.25,
+                Node::Leaf(1),
+                split(Orientation::Vertical, 0.25, Node::Leaf(2), Node::Leaf(3)),
+            )
+        );
+        assert!(node.resize(1, Direction::Right, 1.0));
+        let Node::Split { ratio, .. } = node else {
+            panic!("the root is a split");
+        };
+        assert_eq!(ratio, MAX_SPLIT_RATIO);
+        // Window 1 has no edge on the left.
+        assert!(!tree().resize(1, Direction::Left, 0.25));
+        assert!(!Node::Leaf(1).resize(1, Direction::Right, 0.25));
+    }
+
+    #[test]
+    fn cells_follow_the_tree() {
+        let mut cells = Vec::new();
+        tree().cells(area(), 10, &mut cells);
+        assert_eq!(
+            cells,
+            vec![
+                (1, WindowGeometry::new(0, 0, 495, 500)),
+                (2, WindowGeometry::new(505, 0, 495, 245)),
+                (3, WindowGeometry::new(505, 255, 495, 245)),
+            ]
+        );
+    }
+
+    #[test]
+    fn new_windows_split_the_longer_side_of_the_focused_one() {
+        let mut compositor = BspCompositor::new(TilingConfig::default(), 0);
+        compositor.locate_windows(area(), &clients(4));
+        assert_eq!(
+            compositor.tree,
+            Some(split(
+                Orientation::Horizontal,
+                0.5,
+                Node::Leaf(1),
+                split(
+                    Orientation::Horizontal,
+                    0.5,
+                    Node::Leaf(2),
+                    split(Orientation::Vertical, 0.5, Node::Leaf(3), Node::Leaf(4)),
+                ),
+            ))
+        );
+        // Closed windows leave the tree.
+        let placements = compositor.locate_windows(area(), &clients(2));
+        assert_eq!(
+            geometries(&placements),
+            vec![
+                WindowGeometry::new(0, 0, 500, 500),
+                WindowGeometry::new(500, 0, 500, 500),
+            ]
+        );
+    }
+
+    #[test]
+    fn preselection_applies_to_the_next_window() {
+        let mut compositor = BspCompositor::new(TilingConfig::default(), 0);
+        compositor.locate_windows(area(), &clients(1));
+        compositor.handle_message(LayoutMessage::Preselect(Direction::Left, 0.25), None);
+        let placements = compositor.locate_windows(area(), &clients(2));
+        assert_eq!(
+            geometries(&placements),
+            vec![
+                WindowGeometry::new(250, 0, 750, 500),
+                WindowGeometry::new(0, 0, 250, 500),
+            ]
+        );
+        compositor.handle_message(LayoutMessage::Preselect(Direction::Down, 0.5), None);
+        compositor.handle_message(LayoutMessage::CancelPreselection, None);
+        compositor.locate_windows(area(), &clients(3));
+        assert_eq!(
+            compositor.tree,
+            Some(split(
+                Orientation::Horizontal,
+                0.25,
+                Node::Leaf(2),
+                split(Orientation::Horizontal, 0.5, Node::Leaf(1), Node::Leaf(3)),
+            ))
+        );
+    }
+
+    #[test]
+    fn messages_change_the_subtree_of_the_focused_window() {
+        let mut compositor = BspCompositor::new(TilingConfig::default(), 0);
+        compositor.tree = Some(tree());
+        compositor.handle_message(LayoutMessage::Flip(Orientation::Vertical), Some(window(2)));
+        compositor.handle_message(LayoutMessage::Rotate(true), Some(window(1)));
+        assert_eq!(
+            compositor.tree,
+            Some(split(
+                Orientation::Vertical,
+                0.5,
+                Node::Leaf(1),
+                split(Orientation::Horizontal, 0.5, Node::Leaf(2), Node::Leaf(3)),
+            ))
+        );
+    }
+
+    #[test]
+    fn tree_survives_a_restart() {
+        let mut compositor = BspCompositor::new(TilingConfig::default(), 0);
+        compositor.tree = Some(tree());
+        let state = compositor.save_state().unwrap();
+        let mut restored = BspCompositor::new(TilingConfig::default(), 0);
+        restored.restore_state(&state);
+        assert_eq!(restored.tree, Some(tree()));
+        restored.restore_state("not a tree");
+        assert_eq!(restored.tree, Some(tree()));
+        let duplicate = BspCompositor {
+            tree: Some(split(
+                Orientation::Horizontal,
+                0.5,
+                Node::Leaf(1),
+                Node::Leaf(1),
+            )),
+            ..BspCompositor::new(TilingConfig::default(), 0)
+        };
+        restored.restore_state(&duplicate.save_state().unwrap());
+        assert_eq!(restored.tree, Some(tree()));
+    }
+}
@@ -0,0 +1,210 @@
+use xcb::x;
+
+use super::{
+    compositor::{Client, Compositor, LayoutMessage, Placement},
+    tiling::{change_gap, place, shrink, split, tiled, MAX_MASTER_RATIO, MIN_MASTER_RATIO},
+};
+use crate::{config::TilingConfig, window::WindowGeometry};
+
+/// The master windows are in the middle of the area, the other windows are
+/// stacked on both sides of them. Made for ultrawide monitors.
+pub struct CenteredMasterCompositor {
+    master_count: usize,
+    master_ratio: f32,
+    border_size: u16,
+    inner_gap: u16,
+    outer_gap: u16,
+    smart_borders: bool,
+    smart_gaps: bool,
+}
+
+impl CenteredMasterCompositor {
+    pub fn new(tiling_config: TilingConfig, border_size: u16) -> CenteredMasterCompositor {
+        let gaps_config = tiling_config.gaps_config();
+        CenteredMasterCompositor {
+            master_count: tiling_config.master_count(),
+            master_ratio: tiling_config
+                .master_ratio()
+                .clamp(MIN_MASTER_RATIO, MAX_MASTER_RATIO),
+            border_size,
+            inner_gap: gaps_config.as_ref().map_or(0, |gaps| gaps.inner_size()),
+            outer_gap: gaps_config.as_ref().map_or(0, |gaps| gaps.outer_size()),
+            smart_borders: tiling_config.smart_borders(),
+            smart_gaps: tiling_config.smart_gaps(),
+        }
+    }
+}
+
+/// Cells of `count` windows, masters first. Stacked windows go to the right
+/// and to the left of the masters in turn, a single stacked window is on the
+/// right of the masters, which then aren't centered.
+pub fn centered_master(
+    area: WindowGeometry,
+    count: usize,
+    master_count: usize,
+    master_ratio: f32,
+    gap: u16,
+) -> Vec<WindowGeometry> {
+    let masters = master_count.min(count);
+    let stacked = count - masters;
+    if masters == 0 || stacked == 0 {
+        return split(area, count, false, gap);
+    }
+    let columns = match stacked {
+        1 => 2,
+        _ => 3,
+    };
+    let available = area.width().saturating_sub(gap.saturating_mul(columns - 1));
+    let master_width = (available as f32 * master_ratio).round() as u16;
+    let left_width = match stacked {
+        1 => 0,
+        _ => (available - master_width) / 2,
+    };
+    let right_width = available - master_width - left_width;
+    let column = |x: u16, width: u16| {
+        WindowGeometry::new(
+            area.x().saturating_add_unsigned(x),
+            area.y(),
+            width.max(1),
+            area.height(),
+        )
+    };
+    let left_offset = match stacked {
+        1 => 0,
+        _ => left_width.saturating_add(gap),
+    };
+    let master_area = column(left_offset, master_width);
+    let right_area = column(
+        left_offset.saturating_add(master_width).saturating_add(gap),
+        right_width,
+    );
+    let right = split(right_area, stacked.div_ceil(2), true, gap);
+    let left = split(column(0, left_width), stacked / 2, true, gap);
+    let mut cells = split(master_area, masters, true, gap);
+    cells.extend((0..stacked).map(|index| match index % 2 {
+        0 => right[index / 2],
+        _ => left[index / 2],
+    }));
+    cells
+}
+
+impl Compositor for CenteredMasterCompositor {
+    fn locate_windows(&mut self, area: WindowGeometry, clients: &[Client]) -> Vec<Placement> {
+        let clients = tiled(clients);
+        let area = match clients.len() == 1 && self.smart_gaps {
+            true => area,
+            false => shrink(area, self.outer_gap),
+        };
+        let cells = centered_master(
+            area,
+            clients.len(),
+            self.master_count,
+            self.master_ratio,
+            self.inner_gap,
+        );
+        place(&clients, cells, self.border_size, self.smart_borders)
+    }
+
+    fn name(&self, _focused: Option<usize>, _count: usize) -> String {
+        "|M|".to_string()
+    }
+
+    fn handle_message(&mut self, message: LayoutMessage, _focused: Option<x::Window>) {
+        match message {
+            LayoutMessage::ChangeMasterCount(delta) => {
+                self.master_count = self.master_count.saturating_add_signed(delta as isize)
+            }
+            LayoutMessage::SetMasterCount(count) => self.master_count = count,
+            LayoutMessage::ChangeMasterRatio(delta) => {
+                self.master_ratio =
+                    (self.master_ratio + delta).clamp(MIN_MASTER_RATIO, MAX_MASTER_RATIO)
+            }
+            LayoutMessage::SetMasterRatio(ratio) => {
+                self.master_ratio = ratio.clamp(MIN_MASTER_RATIO, MAX_MASTER_RATIO)
+            }
+            LayoutMessage::ChangeGaps(delta) => {
+                self.inner_gap = change_gap(self.inner_gap, delta);
+                self.outer_gap = change_gap(self.outer_gap, delta);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::MAX_GAP, window_manager::compositor::tests::cells};
+
+    fn area() -> WindowGeometry {
+        WindowGeometry::new(0, 0, 1200, 600)
+    }
+
+    #[test]
+    fn stacked_windows_alternate_right_and_left() {
+        assert_eq!(centered_master(area(), 1, 1, 0.5, 0), vec![area()]);
+        assert_eq!(
+            centered_master(area(), 3, 1, 0.5, 0),
+            cells(&[(300, 0, 600, 600), (900, 0, 300, 600), (0, 0, 300, 600)])
+        );
+        assert_eq!(
+            centered_master(area(), 4, 1, 0.5, 0),
+            cells(&[
+                (300, 0, 600, 600),
+                (900, 0, 300, 300),
+                (0, 0, 300, 600),
+                (900, 300, 300, 300),
+            ])
+        );
+        assert_eq!(
+            centered_master(area(), 5, 1, 0.5, 0),
+            cells(&[
+                (300, 0, 600, 600),
+                (900, 0, 300, 300),
+                (0, 0, 300, 300),
+                (900, 300, 300, 300),
+                (0, 300, 300, 300),
+            ])
+        );
+    }
+
+    #[test]
+    fn single_stacked_window_is_on_the_right() {
+        assert_eq!(
+            centered_master(area(), 2, 1, 0.5, 0),
+            cells(&[(0, 0, 600, 600), (600, 0, 600, 600)])
+        );
+        assert_eq!(
+            centered_master(area(), 2, 1, 0.75, 0),
+            cells(&[(0, 0, 900, 600), (900, 0, 300, 600)])
+        );
+    }
+
+    #[test]
+    fn windows_are_side_by_side_without_a_stack_or_masters() {
+        let expected = cells(&[(0, 0, 400, 600), (400, 0, 400, 600), (800, 0, 400, 600)]);
+        assert_eq!(centered_master(area(), 3, 0, 0.5, 0), expected);
+        assert_eq!(centered_master(area(), 3, 4, 0.5, 0), expected);
+    }
+
+    #[test]
+    fn gaps_separate_the_columns() {
+        let area = WindowGeometry::new(0, 0, 1220, 600);
+        assert_eq!(
+            centered_master(area, 3, 1, 0.5, 10),
+            cells(&[(310, 0, 600, 600), (920, 0, 300, 600), (0, 0, 300, 600)])
+        );
+    }
+
+    #[test]
+    fn large_gaps_and_counts_are_survived() {
+        let area = WindowGeometry::new(i16::MAX - 10, 0, u16::MAX, 600);
+        let cells = centered_master(area, 5, 1, 0.5, MAX_GAP);
+        assert_eq!(cells.len(), 5);
+        assert!(cells.iter().all(|cell| cell.width() >= 1));
+
+        let mut compositor = CenteredMasterCompositor::new(TilingConfig::default(), 0);
+        compositor.handle_message(LayoutMessage::ChangeMasterCount(i32::MIN), None);
+        assert_eq!(compositor.master_count, 0);
+    }
+}
@@ -0,0 +1,213 @@
+//! Layouts don't talk to the X server: they get the usable area and the
+//! visible windows of a tag, and return where the windows go. The window
+//! manager applies the result.
+
+use xcb::x;
+
+use super::{bsp::Orientation, containers::ContainerLayout, direction::Direction};
+use crate::{
+    config::MasterPosition,
+    window::{icccm::SizeHints, WindowGeometry},
+};
+
+pub trait Compositor {
+    /// Places the clients in the area. Floating clients that a layout doesn't
+    /// place are left to the floating layout. Layouts that keep state between
+    /// calls update it here.
+    fn locate_windows(&mut self, area: WindowGeometry, clients: &[Client]) -> Vec<Placement>;
+
+    /// Shown by bars, `focused` is the index of the focused window among the
+    /// `count` placed ones.
+    fn name(&self, focused: Option<usize>, count: usize) -> String;
+
+    /// Changes a parameter of the layout, layouts ignore messages that don't
+    /// apply to them.
+    fn handle_message(&mut self, _message: LayoutMessage, _focused: Option<x::Window>) {}
+
+    /// State that has to survive an in-place restart, as TOML.
+    fn save_state(&self) -> Option<String> {
+        None
+    }
+
+    fn restore_state(&mut self, _state: &str) {}
+
+    /// The placement depends on the focused window, so a focus change
+    /// arranges the windows again.
+    fn follows_focus(&self) -> bool {
+        false
+    }
+
+    /// Titles of tabbed and stacked windows found by the last
+    /// `locate_windows`, drawn by the window manager.
+    fn title_bars(&self) -> Vec<TitleBar> {
+        Vec::new()
+    }
+
+    /// Tiled windows that the last `locate_windows` put behind another
+    /// window, the window manager keeps them below the shown ones.
+    fn hidden_windows(&self) -> Vec<x::Window> {
+        Vec::new()
+    }
+
+    /// Every window floats in floating layouts.
+    fn is_floating(&self) -> bool {
+        false
+    }
+}
+
+/// What a layout knows about a window.
+#[derive(Clone, Copy)]
+pub struct Client {
+    window: x::Window,
+    size_hints: SizeHints,
+    floating: bool,
+    focused: bool,
+    floating_geometry: Option<WindowGeometry>,
+}
+
+impl Client {
+    pub fn new(
+        window: x::Window,
+        size_hints: SizeHints,
+        floating: bool,
+        focused: bool,
+        floating_geometry: Option<WindowGeometry>,
+    ) -> Client {
+        Client {
+            window,
+            size_hints,
+            floating,
+            focused,
+            floating_geometry,
+        }
+    }
+
+    pub fn window(&self) -> x::Window {
+        self.window
+    }
+
+    pub fn size_hints(&self) -> SizeHints {
+        self.size_hints
+    }
+
+    pub fn is_floating(&self) -> bool {
+        self.floating
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Geometry of the window while it floats, only known for floating
+    /// clients.
+    pub fn floating_geometry(&self) -> Option<WindowGeometry> {
+        self.floating_geometry
+    }
+}
+
+/// Where a layout puts a window, the geometry doesn't include the border.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Placement {
+    window: x::Window,
+    geometry: WindowGeometry,
+    border_size: u16,
+}
+
+impl Placement {
+    pub fn new(window: x::Window, geometry: WindowGeometry, border_size: u16) -> Placement {
+        Placement {
+            window,
+            geometry,
+            border_size,
+        }
+    }
+
+    pub fn window(&self) -> x::Window {
+        self.window
+    }
+
+    pub fn geometry(&self) -> WindowGeometry {
+        self.geometry
+    }
+
+    pub fn border_size(&self) -> u16 {
+        self.border_size
+    }
+}
+
+/// Title of a window, clicking it focuses the window.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TitleBar {
+    window: x::Window,
+    geometry: WindowGeometry,
+}
+
+impl TitleBar {
+    pub fn new(window: x::Window, geometry: WindowGeometry) -> TitleBar {
+        TitleBar { window, geometry }
+    }
+
+    pub fn window(&self) -> x::Window {
+        self.window
+    }
+
+    pub fn geometry(&self) -> WindowGeometry {
+        self.geometry
+    }
+}
+
+/// Runtime changes of layout parameters.
+#[derive(Clone, Copy)]
+pub enum LayoutMessage {
+    ChangeMasterCount(i32),
+    SetMasterCount(usize),
+    ChangeMasterRatio(f32),
+    SetMasterRatio(f32),
+    SetMasterPosition(MasterPosition),
+    /// Grows or shrinks the inner and outer gaps.
+    ChangeGaps(i32),
+    /// Side and ratio of the next split of the focused window.
+    Preselect(Direction, f32),
+    CancelPreselection,
+    /// Turns the split of the focused window, clockwise if `true`.
+    Rotate(bool),
+    Flip(Orientation),
+    Balance,
+    /// Moves the edge of the focused window.
+    Resize(Direction, f32),
+    SetContainerLayout(ContainerLayout),
+    ToggleSplit,
+    /// Puts the selection in a new container.
+    Split(Orientation),
+    /// Moves the focused window, or the selected container, within the layout.
+    Move(Direction),
+    /// Selects the container of the selection, or goes back down.
+    FocusParent,
+    FocusChild,
+}
+
+#[cfg(test)]
+pub mod tests {
+    use xcb::{x, XidNew};
+
+    use super::Client;
+    use crate::window::{icccm::SizeHints, WindowGeometry};
+
+    pub fn window(id: u32) -> x::Window {
+        x::Window::new(id)
+    }
+
+    /// Tiled clients of the windows 1 to `count`, without size hints.
+    pub fn clients(count: u32) -> Vec<Client> {
+        (1..=count)
+            .map(|id| Client::new(window(id), SizeHints::default(), false, false, None))
+            .collect()
+    }
+
+    pub fn cells(geometries: &[(i16, i16, u16, u16)]) -> Vec<WindowGeometry> {
+        geometries
+            .iter()
+            .map(|(x, y, width, height)| WindowGeometry::new(*x, *y, *width, *height))
+            .collect()
+    }
+}
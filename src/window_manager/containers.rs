@@ -0,0 +1,814 @@
+//! i3 style layout: windows are nested in containers, which place their
+//! children side by side, one above the other, or as tabs. Containers are
+//! created by splitting a window and removed when they become empty.
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use xcb::{x, Xid, XidNew};
+
+use super::{
+    bsp::Orientation,
+    compositor::{Client, Compositor, LayoutMessage, Placement, TitleBar},
+    direction::Direction,
+    tiling::{change_gap, place, shrink, split, tiled},
+};
+use crate::{config::TilingConfig, window::WindowGeometry};
+
+/// Height of a title in tabbed and stacked containers.
+pub const TITLE_HEIGHT: u16 = 18;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ContainerLayout {
+    SplitH,
+    SplitV,
+    /// One child is shown at a time, the titles are side by side.
+    Tabbed,
+    /// One child is shown at a time, the titles are one above the other.
+    Stacked,
+}
+
+impl ContainerLayout {
+    /// Children follow each other from left to right, rather than from top to
+    /// bottom.
+    fn is_horizontal(&self) -> bool {
+        matches!(self, ContainerLayout::SplitH | ContainerLayout::Tabbed)
+    }
+}
+
+impl FromStr for ContainerLayout {
+    type Err = String;
+
+    fn from_str(layout: &str) -> Result<Self, Self::Err> {
+        Ok(match layout {
+            "splith" => ContainerLayout::SplitH,
+            "splitv" => ContainerLayout::SplitV,
+            "tabbed" => ContainerLayout::Tabbed,
+            "stacked" => ContainerLayout::Stacked,
+            _ => return Err(format!("unknown container layout `{}`", layout)),
+        })
+    }
+}
+
+/// Nodes are found by their path, the indices of the children from the root
+/// down to the node.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Container {
+    Window(u32),
+    Split {
+        layout: ContainerLayout,
+        children: Vec<Container>,
+        /// Child that was focused last, it's the one shown in tabbed and
+        /// stacked containers.
+        focused: usize,
+    },
+}
+
+impl Container {
+    pub fn new(layout: ContainerLayout, children: Vec<Container>) -> Container {
+        Container::Split {
+            layout,
+            children,
+            focused: 0,
+        }
+    }
+
+    pub fn windows(&self) -> Vec<u32> {
+        match self {
+            Container::Window(window) => vec![*window],
+            Container::Split { children, .. } => {
+                children.iter().flat_map(|child| child.windows()).collect()
+            }
+        }
+    }
+
+    pub fn find(&self, window: u32) -> Option<Vec<usize>> {
+        match self {
+            Container::Window(leaf) if *leaf == window => Some(Vec::new()),
+            Container::Window(_) => None,
+            Container::Split { children, .. } => {
+                children.iter().enumerate().find_map(|(index, child)| {
+                    let mut path = child.find(window)?;
+                    path.insert(0, index);
+                    Some(path)
+                })
+            }
+        }
+    }
+
+    pub fn get_mut(&mut self, path: &[usize]) -> &mut Container {
+        match (self, path.split_first()) {
+            (Container::Split { children, .. }, Some((index, rest))) => {
+                children[*index].get_mut(rest)
+            }
+            (container, _) => container,
+        }
+    }
+
+    fn layout_mut(&mut self) -> Option<&mut ContainerLayout> {
+        match self {
+            Container::Window(_) => None,
+            Container::Split { layout, .. } => Some(layout),
+        }
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Container>> {
+        match self {
+            Container::Window(_) => None,
+            Container::Split { children, .. } => Some(children),
+        }
+    }
+
+    /// Window shown for the container, following the focused children.
+    pub fn active_window(&self) -> Option<u32> {
+        match self {
+            Container::Window(window) => Some(*window),
+            Container::Split {
+                children, focused, ..
+            } => children.get(*focused)?.active_window(),
+        }
+    }
+
+    /// Makes the node at the path the focused child of every container above
+    /// it.
+    pub fn focus(&mut self, path: &[usize]) {
+        if let (
+            Container::Split {
+                children, focused, ..
+            },
+            Some((index, rest)),
+        ) = (self, path.split_first())
+        {
+            *focused = *index;
+            children[*index].focus(rest);
+        }
+    }
+
+    /// Takes the node out of the tree. Containers left empty are removed,
+    /// except the root. `None` if there is no node at the path, or if it's the
+    /// root.
+    pub fn take(&mut self, path: &[usize]) -> Option<Container> {
+        let Container::Split {
+            children, focused, ..
+        } = self
+        else {
+            return None;
+        };
+        let (index, rest) = path.split_first()?;
+        if *index >= children.len() {
+            return None;
+        }
+        let node = match rest.is_empty() {
+            true => children.remove(*index),
+            false => {
+                let node = children[*index].take(rest)?;
+                if children[*index].windows().is_empty() {
+                    children.remove(*index);
+                }
+                node
+            }
+        };
+        *focused = (*focused).min(children.len().saturating_sub(1));
+        Some(node)
+    }
+
+    /// Whether the tree can be used by the layout: the root is a container,
+    /// the other containers aren't empty and every window is in the tree
+    /// once.
+    fn is_valid_root(&self) -> bool {
+        let mut windows = self.windows();
+        let count = windows.len();
+        windows.sort_unstable();
+        windows.dedup();
+        self.is_valid(true) && windows.len() == count
+    }
+
+    fn is_valid(&self, root: bool) -> bool {
+        match self {
+            Container::Window(_) => !root,
+            Container::Split {
+                children, focused, ..
+            } => {
+                (root || !children.is_empty())
+                    && (*focused < children.len() || children.is_empty())
+                    && children.iter().all(|child| child.is_valid(false))
+            }
+        }
+    }
+
+    /// Cells of the windows and titles of the shown children. Only the
+    /// focused child of tabbed and stacked containers is `visible`, the others
+    /// are `hidden` behind it and show no titles.
+    fn cells(
+        &self,
+        area: WindowGeometry,
+        gap: u16,
+        visible: bool,
+        cells: &mut Vec<(u32, WindowGeometry)>,
+        titles: &mut Vec<(u32, WindowGeometry)>,
+        hidden: &mut Vec<u32>,
+    ) {
+        let (layout, children, focused) = match self {
+            Container::Window(window) => {
+                cells.push((*window, area));
+                if !visible {
+                    hidden.push(*window);
+                }
+                return;
+            }
+            Container::Split {
+                layout,
+                children,
+                focused,
+            } => (layout, children, *focused),
+        };
+        let count = children.len();
+        if count == 0 {
+            return;
+        }
+        let strip_height = match layout {
+            ContainerLayout::SplitH | ContainerLayout::SplitV => {
+                let cells_areas = split(area, count, *layout == ContainerLayout::SplitV, gap);
+                for (child, cell) in children.iter().zip(cells_areas) {
+                    child.cells(cell, gap, visible, cells, titles, hidden);
+                }
+                return;
+            }
+            ContainerLayout::Tabbed => TITLE_HEIGHT,
+            ContainerLayout::Stacked => TITLE_HEIGHT.saturating_mul(count as u16),
+        }
+        .min(area.height().saturating_sub(1));
+        let strip = WindowGeometry::new(area.x(), area.y(), area.width(), strip_height.max(1));
+        let content = WindowGeometry::new(
+            area.x(),
+            area.y() + strip_height as i16,
+            area.width(),
+            area.height().saturating_sub(strip_height).max(1),
+        );
+        let title_cells = split(strip, count, !layout.is_horizontal(), 0);
+        for (index, (child, title)) in children.iter().zip(title_cells).enumerate() {
+            if let (true, Some(window)) = (visible, child.active_window()) {
+                titles.push((window, title));
+            }
+            child.cells(
+                content,
+                gap,
+                visible && index == focused,
+                cells,
+                titles,
+                hidden,
+            );
+        }
+    }
+}
+
+pub struct ContainerCompositor {
+    root: Container,
+    /// Focused window and how many levels above it the selection is, the
+    /// selected node is what moves and gets split.
+    selection: Option<(u32, usize)>,
+    titles: Vec<TitleBar>,
+    hidden: Vec<x::Window>,
+    border_size: u16,
+    inner_gap: u16,
+    outer_gap: u16,
+    smart_borders: bool,
+    smart_gaps: bool,
+}
+
+/// Saved on restart.
+#[derive(Serialize, Deserialize)]
+struct ContainersState {
+    root: Container,
+}
+
+impl ContainerCompositor {
+    pub fn new(tiling_config: TilingConfig, border_size: u16) -> ContainerCompositor {
+        let gaps_config = tiling_config.gaps_config();
+        ContainerCompositor {
+            root: Container::new(ContainerLayout::SplitH, Vec::new()),
+            selection: None,
+            titles: Vec::new(),
+            hidden: Vec::new(),
+            border_size,
+            inner_gap: gaps_config.as_ref().map_or(0, |gaps| gaps.inner_size()),
+            outer_gap: gaps_config.as_ref().map_or(0, |gaps| gaps.outer_size()),
+            smart_borders: tiling_config.smart_borders(),
+            smart_gaps: tiling_config.smart_gaps(),
+        }
+    }
+
+    /// A new window goes next to the focused one, in the same container.
+    fn insert(&mut self, window: u32, focused: Option<u32>) {
+        let target = focused
+            .and_then(|focused| self.root.find(focused))
+            .or_else(|| {
+                let last = *self.root.windows().last()?;
+                self.root.find(last)
+            });
+        match target.as_deref().and_then(|path| path.split_last()) {
+            Some((index, parent)) => {
+                if let Some(children) = self.root.get_mut(parent).children_mut() {
+                    children.insert(index + 1, Container::Window(window));
+                }
+            }
+            None => {
+                if let Some(children) = self.root.children_mut() {
+                    children.push(Container::Window(window));
+                }
+            }
+        }
+    }
+
+    /// Path of the selected node, the focused window or a container above it.
+    fn selected(&self, focused: Option<x::Window>) -> Option<Vec<usize>> {
+        let focused = focused?.resource_id();
+        let mut path = self.root.find(focused)?;
+        let level = match self.selection {
+            Some((window, level)) if window == focused => level,
+            _ => 0,
+        };
+        path.truncate(path.len().saturating_sub(level));
+        Some(path)
+    }
+
+    fn change_level(&mut self, focused: Option<x::Window>, parent: bool) {
+        let Some(focused) = focused.map(|focused| focused.resource_id()) else {
+            return;
+        };
+        let Some(depth) = self.root.find(focused).map(|path| path.len()) else {
+            return;
+        };
+        let level = match self.selection {
+            Some((window, level)) if window == focused => level,
+            _ => 0,
+        };
+        let level = match parent {
+            true => (level + 1).min(depth),
+            false => level.saturating_sub(1),
+        };
+        self.selection = Some((focused, level));
+    }
+
+    /// Layout of the container the selected node is in, or of the selected
+    /// container itself.
+    fn set_layout(
+        &mut self,
+        focused: Option<x::Window>,
+        change: impl Fn(ContainerLayout) -> ContainerLayout,
+    ) {
+        let Some(mut path) = self.selected(focused) else {
+            return;
+        };
+        if matches!(self.root.get_mut(&path), Container::Window(_)) {
+            path.pop();
+        }
+        if let Some(layout) = self.root.get_mut(&path).layout_mut() {
+            *layout = change(*layout);
+        }
+    }
+
+    /// Puts the selected node in a new container, where the next window will
+    /// go. A window that is alone in its container only changes the layout of
+    /// the container.
+    fn split(&mut self, focused: Option<x::Window>, orientation: Orientation) {
+        let layout = match orientation {
+            Orientation::Horizontal => ContainerLayout::SplitH,
+            Orientation::Vertical => ContainerLayout::SplitV,
+        };
+        let Some(path) = self.selected(focused) else {
+            return;
+        };
+        if let Some((_, parent)) = path.split_last() {
+            let alone = self
+                .root
+                .get_mut(parent)
+                .children_mut()
+                .is_some_and(|children| children.len() == 1);
+            if alone {
+                if let Some(old) = self.root.get_mut(parent).layout_mut() {
+                    *old = layout;
+                }
+                return;
+            }
+        }
+        let node = self.root.get_mut(&path);
+        let wrapped = std::mem::replace(node, Container::new(layout, Vec::new()));
+        if let Some(children) = node.children_mut() {
+            children.push(wrapped);
+        }
+    }
+
+    /// Moves the selected node past its neighbour in the direction, into the
+    /// neighbour if it's a container. At the edge of its container, the node
+    /// moves out to the closest container above that is laid out in the
+    /// direction.
+    fn move_node(&mut self, focused: Option<x::Window>, direction: Direction) {
+        let Some(path) = self.selected(focused) else {
+            return;
+        };
+        let Some((&index, parent_path)) = path.split_last() else {
+            return;
+        };
+        let horizontal = matches!(direction, Direction::Left | Direction::Right);
+        let forward = matches!(direction, Direction::Right | Direction::Down);
+        let parent = self.root.get_mut(parent_path);
+        let Container::Split {
+            layout, children, ..
+        } = parent
+        else {
+            return;
+        };
+        if layout.is_horizontal() == horizontal {
+            let neighbour = match forward {
+                true => Some(index + 1).filter(|neighbour| *neighbour < children.len()),
+                false => index.checked_sub(1),
+            };
+            if let Some(neighbour) = neighbour {
+                if let Container::Window(_) = children[neighbour] {
+                    children.swap(index, neighbour);
+                } else {
+                    let node = children.remove(index);
+                    let neighbour = match neighbour > index {
+                        true => neighbour - 1,
+                        false => neighbour,
+                    };
+                    if let Some(inner) = children[neighbour].children_mut() {
+                        match forward {
+                            true => inner.insert(0, node),
+                            false => inner.push(node),
+                        }
+                    }
+                }
+                self.refocus(focused);
+                return;
+            }
+        }
+        // The container the node moves to keeps other windows, so it isn't
+        // removed when the node is taken out.
+        let moved = self.root.get_mut(&path).windows().len();
+        let ancestor = (0..parent_path.len()).rev().find(|depth| {
+            let ancestor = self.root.get_mut(&path[..*depth]);
+            ancestor.windows().len() > moved
+                && matches!(
+                    ancestor,
+                    Container::Split { layout, .. } if layout.is_horizontal() == horizontal
+                )
+        });
+        match ancestor {
+            Some(depth) => {
+                let ancestor_path = &path[..depth];
+                let child = path[depth];
+                let count = self.ancestor_children(ancestor_path);
+                let Some(node) = self.root.take(&path) else {
+                    return;
+                };
+                let position = match self.ancestor_children(ancestor_path) < count {
+                    true => child,
+                    false if forward => child + 1,
+                    false => child,
+                };
+                if let Some(children) = self.root.get_mut(ancestor_path).children_mut() {
+                    children.insert(position, node);
+                }
+            }
+            None => {
+                let root_matches = matches!(
+                    &self.root,
+                    Container::Split { layout, .. } if layout.is_horizontal() == horizontal
+                );
+                if root_matches || self.root.windows().len() == moved {
+                    return;
+                }
+                let Some(node) = self.root.take(&path) else {
+                    return;
+                };
+                let layout = match horizontal {
+                    true => ContainerLayout::SplitH,
+                    false => ContainerLayout::SplitV,
+                };
+                let old = std::mem::replace(&mut self.root, Container::new(layout, Vec::new()));
+                let children = match forward {
+                    true => vec![old, node],
+                    false => vec![node, old],
+                };
+                self.root = Container::new(layout, children);
+            }
+        }
+        self.refocus(focused);
+    }
+
+    fn ancestor_children(&mut self, path: &[usize]) -> usize {
+        self.root
+            .get_mut(path)
+            .children_mut()
+            .map_or(0, |children| children.len())
+    }
+
+    fn refocus(&mut self, focused: Option<x::Window>) {
+        if let Some(path) = focused.and_then(|focused| self.root.find(focused.resource_id())) {
+            self.root.focus(&path);
+        }
+    }
+}
+
+impl Compositor for ContainerCompositor {
+    fn locate_windows(&mut self, area: WindowGeometry, clients: &[Client]) -> Vec<Placement> {
+        let clients = tiled(clients);
+        let windows: Vec<u32> = clients
+            .iter()
+            .map(|client| client.window().resource_id())
+            .collect();
+        for window in self.root.windows() {
+            if !windows.contains(&window) {
+                if let Some(path) = self.root.find(window) {
+                    self.root.take(&path);
+                }
+            }
+        }
+        let focused = clients
+            .iter()
+            .find(|client| client.is_focused())
+            .map(|client| client.window().resource_id());
+        for window in &windows {
+            if self.root.find(*window).is_none() {
+                self.insert(*window, focused);
+            }
+        }
+        if let Some(path) = focused.and_then(|focused| self.root.find(focused)) {
+            self.root.focus(&path);
+        }
+        let area = match clients.len() == 1 && self.smart_gaps {
+            true => area,
+            false => shrink(area, self.outer_gap),
+        };
+        let mut cells = Vec::new();
+        let mut titles = Vec::new();
+        let mut hidden = Vec::new();
+        self.root.cells(
+            area,
+            self.inner_gap,
+            true,
+            &mut cells,
+            &mut titles,
+            &mut hidden,
+        );
+        self.titles = titles
+            .into_iter()
+            .map(|(window, geometry)| TitleBar::new(x::Window::new(window), geometry))
+            .collect();
+        self.hidden = hidden.into_iter().map(x::Window::new).collect();
+        let cells = windows
+            .iter()
+            .map(|window| {
+                cells
+                    .iter()
+                    .find(|(leaf, _)| leaf == window)
+                    .map_or(area, |(_, cell)| *cell)
+            })
+            .collect();
+        place(&clients, cells, self.border_size, self.smart_borders)
+    }
+
+    fn name(&self, _focused: Option<usize>, _count: usize) -> String {
+        "[C]".to_string()
+    }
+
+    fn handle_message(&mut self, message: LayoutMessage, focused: Option<x::Window>) {
+        match message {
+            LayoutMessage::SetContainerLayout(layout) => self.set_layout(focused, |_| layout),
+            LayoutMessage::ToggleSplit => self.set_layout(focused, |layout| match layout {
+                ContainerLayout::SplitH => ContainerLayout::SplitV,
+                _ => ContainerLayout::SplitH,
+            }),
+            LayoutMessage::Split(orientation) => self.split(focused, orientation),
+            LayoutMessage::Move(direction) => self.move_node(focused, direction),
+            LayoutMessage::FocusParent => self.change_level(focused, true),
+            LayoutMessage::FocusChild => self.change_level(focused, false),
+            LayoutMessage::ChangeGaps(delta) => {
+                self.inner_gap = change_gap(self.inner_gap, delta);
+                self.outer_gap = change_gap(self.outer_gap, delta);
+            }
+            _ => {}
+        }
+    }
+
+    fn title_bars(&self) -> Vec<TitleBar> {
+        self.titles.clone()
+    }
+
+    fn hidden_windows(&self) -> Vec<x::Window> {
+        self.hidden.clone()
+    }
+
+    fn save_state(&self) -> Option<String> {
+        toml::to_string(&ContainersState {
+            root: self.root.clone(),
+        })
+        .ok()
+    }
+
+    fn restore_state(&mut self, state: &str) {
+        // The state of another version could have a tree the layout can't
+        // work with.
+        if let Ok(state) = toml::from_str::<ContainersState>(state) {
+            if state.root.is_valid_root() {
+                self.root = state.root;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_manager::compositor::tests::{clients, window};
+
+    fn windows(windows: &[u32]) -> Vec<Container> {
+        windows.iter().copied().map(Container::Window).collect()
+    }
+
+    fn focused(layout: ContainerLayout, children: Vec<Container>, focused: usize) -> Container {
+        Container::Split {
+            layout,
+            children,
+            focused,
+        }
+    }
+
+    /// Window 1 on the left, windows 2 and 3 one above the other on the right.
+    fn tree() -> Container {
+        Container::new(
+            ContainerLayout::SplitH,
+            vec![
+                Container::Window(1),
+                Container::new(ContainerLayout::SplitV, windows(&[2, 3])),
+            ],
+        )
+    }
+
+    fn compositor(root: Container) -> ContainerCompositor {
+        let mut compositor = ContainerCompositor::new(TilingConfig::default(), 0);
+        compositor.root = root;
+        compositor
+    }
+
+    #[test]
+    fn take_removes_empty_containers() {
+        let mut root = Container::new(
+            ContainerLayout::SplitH,
+            vec![
+                Container::Window(1),
+                Container::new(ContainerLayout::SplitV, windows(&[2])),
+            ],
+        );
+        assert_eq!(root.take(&[1, 0]), Some(Container::Window(2)));
+        assert_eq!(root, Container::new(ContainerLayout::SplitH, windows(&[1])));
+        assert_eq!(root.take(&[0]), Some(Container::Window(1)));
+        assert_eq!(root, Container::new(ContainerLayout::SplitH, Vec::new()));
+    }
+
+    #[test]
+    fn take_refuses_invalid_paths() {
+        assert_eq!(tree().take(&[]), None);
+        assert_eq!(tree().take(&[2]), None);
+        assert_eq!(tree().take(&[0, 0]), None);
+        assert_eq!(Container::Window(1).take(&[0]), None);
+    }
+
+    #[test]
+    fn move_swaps_with_a_window() {
+        let mut compositor =
+            compositor(Container::new(ContainerLayout::SplitH, windows(&[1, 2, 3])));
+        compositor.handle_message(LayoutMessage::Move(Direction::Right), Some(window(1)));
+        assert_eq!(
+            compositor.root,
+            focused(ContainerLayout::SplitH, windows(&[2, 1, 3]), 1)
+        );
+    }
+
+    #[test]
+    fn move_enters_a_neighbour_container() {
+        let mut compositor = compositor(tree());
+        compositor.handle_message(LayoutMessage::Move(Direction::Right), Some(window(1)));
+        assert_eq!(
+            compositor.root,
+            Container::new(
+                ContainerLayout::SplitH,
+                vec![Container::new(ContainerLayout::SplitV, windows(&[1, 2, 3]))],
+            )
+        );
+    }
+
+    #[test]
+    fn move_leaves_the_container_at_its_edge() {
+        let mut compositor = compositor(tree());
+        compositor.handle_message(LayoutMessage::Move(Direction::Right), Some(window(3)));
+        assert_eq!(
+            compositor.root,
+            focused(
+                ContainerLayout::SplitH,
+                vec![
+                    Container::Window(1),
+                    Container::new(ContainerLayout::SplitV, windows(&[2])),
+                    Container::Window(3),
+                ],
+                2,
+            )
+        );
+    }
+
+    #[test]
+    fn move_out_of_the_root_wraps_it() {
+        let mut compositor = compositor(Container::new(ContainerLayout::SplitV, windows(&[1, 2])));
+        compositor.handle_message(LayoutMessage::Move(Direction::Right), Some(window(1)));
+        assert_eq!(
+            compositor.root,
+            focused(
+                ContainerLayout::SplitH,
+                vec![
+                    Container::new(ContainerLayout::SplitV, windows(&[2])),
+                    Container::Window(1),
+                ],
+                1,
+            )
+        );
+        // Nowhere to go.
+        compositor.handle_message(LayoutMessage::Move(Direction::Right), Some(window(1)));
+        assert_eq!(compositor.root.find(1), Some(vec![1]));
+    }
+
+    #[test]
+    fn focus_parent_and_child_change_the_selection() {
+        let mut compositor = compositor(tree());
+        let focused = Some(window(3));
+        assert_eq!(compositor.selected(focused), Some(vec![1, 1]));
+        compositor.handle_message(LayoutMessage::FocusParent, focused);
+        assert_eq!(compositor.selected(focused), Some(vec![1]));
+        compositor.handle_message(LayoutMessage::FocusParent, focused);
+        compositor.handle_message(LayoutMessage::FocusParent, focused);
+        assert_eq!(compositor.selected(focused), Some(Vec::new()));
+        compositor.handle_message(LayoutMessage::FocusChild, focused);
+        assert_eq!(compositor.selected(focused), Some(vec![1]));
+        // The selected container changes its own layout.
+        compositor.handle_message(
+            LayoutMessage::SetContainerLayout(ContainerLayout::Tabbed),
+            focused,
+        );
+        assert_eq!(
+            compositor.root.get_mut(&[1]).layout_mut(),
+            Some(&mut ContainerLayout::Tabbed)
+        );
+        compositor.handle_message(LayoutMessage::FocusChild, focused);
+        compositor.handle_message(LayoutMessage::FocusChild, focused);
+        assert_eq!(compositor.selected(focused), Some(vec![1, 1]));
+        // The selection belongs to the window it was made for.
+        assert_eq!(compositor.selected(Some(window(1))), Some(vec![0]));
+    }
+
+    #[test]
+    fn tabs_behind_the_shown_one_are_hidden() {
+        let mut compositor = compositor(focused(ContainerLayout::Tabbed, windows(&[1, 2, 3]), 1));
+        let area = WindowGeometry::new(0, 0, 900, 500);
+        let placements = compositor.locate_windows(area, &clients(3));
+        let content = WindowGeometry::new(0, TITLE_HEIGHT as i16, 900, 500 - TITLE_HEIGHT);
+        assert!(placements
+            .iter()
+            .all(|placement| placement.geometry() == content));
+        assert_eq!(compositor.hidden_windows(), vec![window(1), window(3)]);
+        assert_eq!(
+            compositor.title_bars(),
+            vec![
+                TitleBar::new(window(1), WindowGeometry::new(0, 0, 300, TITLE_HEIGHT)),
+                TitleBar::new(window(2), WindowGeometry::new(300, 0, 300, TITLE_HEIGHT)),
+                TitleBar::new(window(3), WindowGeometry::new(600, 0, 300, TITLE_HEIGHT)),
+            ]
+        );
+    }
+
+    #[test]
+    fn restore_rejects_trees_the_layout_cant_use() {
+        let state = |root: Container| toml::to_string(&ContainersState { root }).unwrap();
+        let mut compositor = compositor(Container::new(ContainerLayout::SplitH, Vec::new()));
+        compositor.restore_state(&state(Container::Window(1)));
+        compositor.restore_state(&state(Container::new(
+            ContainerLayout::SplitH,
+            windows(&[1, 1]),
+        )));
+        compositor.restore_state(&state(Container::new(
+            ContainerLayout::SplitH,
+            vec![Container::new(ContainerLayout::SplitV, Vec::new())],
+        )));
+        assert_eq!(
+            compositor.root,
+            Container::new(ContainerLayout::SplitH, Vec::new())
+        );
+        compositor.restore_state(&state(tree()));
+        assert_eq!(compositor.root, tree());
+        // New windows still find their place.
+        compositor.locate_windows(WindowGeometry::new(0, 0, 900, 500), &clients(4));
+        assert_eq!(compositor.root.find(4), Some(vec![1, 2]));
+    }
+}
@@ -0,0 +1,99 @@
+use std::str::FromStr;
+
+use crate::window::WindowGeometry;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(direction: &str) -> Result<Self, Self::Err> {
+        Ok(match direction {
+            "left" => Direction::Left,
+            "right" => Direction::Right,
+            "up" => Direction::Up,
+            "down" => Direction::Down,
+            _ => return Err(format!("unknown direction `{}`", direction)),
+        })
+    }
+}
+
+/// Index of the rectangle next to `from` in the direction. Rectangles that
+/// overlap `from` on the other axis are preferred, then the closest one.
+pub fn neighbour(
+    from: WindowGeometry,
+    rectangles: &[WindowGeometry],
+    direction: Direction,
+) -> Option<usize> {
+    let from = Edges::new(from);
+    rectangles
+        .iter()
+        .enumerate()
+        .map(|(index, rectangle)| (index, Edges::new(*rectangle)))
+        .filter(|(_, to)| *to != from)
+        .filter_map(|(index, to)| {
+            // Along the direction and across it.
+            let (distance, beyond, overlaps, offset) = match direction {
+                Direction::Left => (
+                    from.left - to.right,
+                    to.center_x() < from.left,
+                    to.top < from.bottom && from.top < to.bottom,
+                    (to.center_y() - from.center_y()).abs(),
+                ),
+                Direction::Right => (
+                    to.left - from.right,
+                    to.center_x() > from.right,
+                    to.top < from.bottom && from.top < to.bottom,
+                    (to.center_y() - from.center_y()).abs(),
+                ),
+                Direction::Up => (
+                    from.top - to.bottom,
+                    to.center_y() < from.top,
+                    to.left < from.right && from.left < to.right,
+                    (to.center_x() - from.center_x()).abs(),
+                ),
+                Direction::Down => (
+                    to.top - from.bottom,
+                    to.center_y() > from.bottom,
+                    to.left < from.right && from.left < to.right,
+                    (to.center_x() - from.center_x()).abs(),
+                ),
+            };
+            beyond.then_some((index, (!overlaps, distance.max(0), offset)))
+        })
+        .min_by_key(|(_, score)| *score)
+        .map(|(index, _)| index)
+}
+
+#[derive(PartialEq, Eq)]
+struct Edges {
+    left: i32,
+    top: i32,
+    right: i32,
+    bottom: i32,
+}
+
+impl Edges {
+    fn new(geometry: WindowGeometry) -> Edges {
+        Edges {
+            left: geometry.x() as i32,
+            top: geometry.y() as i32,
+            right: geometry.x() as i32 + geometry.width() as i32,
+            bottom: geometry.y() as i32 + geometry.height() as i32,
+        }
+    }
+
+    fn center_x(&self) -> i32 {
+        (self.left + self.right) / 2
+    }
+
+    fn center_y(&self) -> i32 {
+        (self.top + self.bottom) / 2
+    }
+}
@@ -1,6 +1,47 @@
+use xcb::x;
+
+use self::keyboard_event_handler::{KeyCode, KeyboardEventHandler};
+
+// The key tables and the combination parser are only used by key
+// bindings, which aren't part of this window manager yet.
+#[allow(dead_code)]
 mod key_combinations;
+#[allow(dead_code)]
 mod keyboard_event_handler;
 
-pub enum EventHadlerError {}
+/// Caps Lock and Num Lock, which are ignored when keys and buttons are
+/// matched, so every grab is made for each of these.
+pub const LOCK_MASKS: [x::ModMask; 4] = [
+    x::ModMask::empty(),
+    x::ModMask::LOCK,
+    x::ModMask::N2,
+    x::ModMask::LOCK.union(x::ModMask::N2),
+];
+
+/// Modifiers of an event without the lock modifiers.
+pub fn modifiers(state: x::KeyButMask) -> x::ModMask {
+    x::ModMask::from_bits_truncate(state.bits()).difference(LOCK_MASKS[3])
+}
+
+/// Keeps track of the held keys.
+pub struct EventHandler {
+    keyboard_event_handler: KeyboardEventHandler,
+}
+
+impl EventHandler {
+    pub fn new() -> EventHandler {
+        EventHandler {
+            keyboard_event_handler: KeyboardEventHandler::new(),
+        }
+    }
+
+    pub fn on_key_press(&self, event: &x::KeyPressEvent) {
+        self.keyboard_event_handler.on_press(event);
+    }
 
-pub struct EventHandler {}
+    /// Returns `true` if a modifier was released.
+    pub fn on_key_release(&self, event: &x::KeyReleaseEvent) -> bool {
+        self.keyboard_event_handler.on_relese(event);
+        KeyCode::from(event.detail()).is_modkey()
+    }
+}
@@ -1,5 +1,3 @@
-use std::str::FromStr;
-
 use super::keyboard_event_handler::KeyCode;
 
 pub struct KeyCombination {
@@ -8,8 +6,10 @@ pub struct KeyCombination {
 }
 
 fn parce_combination(combination: String) -> Result<Vec<KeyCode>, ()> {
-    let keys_result: Vec<Result<KeyCode, ()>> =
-        combination.split(" + ").map(|key_string| todo!()).collect();
+    let keys_result: Vec<Result<KeyCode, ()>> = combination
+        .split(" + ")
+        .map(|_key_string| todo!())
+        .collect();
     let mut keys = Vec::new();
     for key in keys_result {
         if let Ok(key) = key {
@@ -1,4 +1,7 @@
-use std::{cell::RefCell, fmt::format};
+use std::{
+    cell::RefCell,
+    fmt::{self, Display, Formatter},
+};
 
 pub struct KeyboardEventHandler {
     input_buffer: RefCell<Vec<KeyCode>>,
@@ -6,28 +9,23 @@ pub struct KeyboardEventHandler {
 
 impl KeyboardEventHandler {
     pub fn new() -> KeyboardEventHandler {
-        let keyboard_event_handler = KeyboardEventHandler {
+        KeyboardEventHandler {
             input_buffer: RefCell::new(Vec::new()),
-        };
-        keyboard_event_handler
-    }
-    fn input_buffer(&self) -> &mut Vec<KeyCode> {
-        self.input_buffer.get_mut()
+        }
     }
-    pub fn on_press(&self, event: xcb::x::KeyPressEvent) {
+    pub fn on_press(&self, event: &xcb::x::KeyPressEvent) {
         self.input_buffer
             .borrow_mut()
             .push(KeyCode::from(event.detail()))
     }
 
-    pub fn on_relese(&self, event: xcb::x::KeyReleaseEvent) {
-        if self.input_buffer().contains(&KeyCode::from(event.detail())) {
-            let index = self
-                .input_buffer()
-                .iter()
-                .position(|key| *key == KeyCode::from(event.detail()))
-                .unwrap();
-            self.input_buffer().remove(index);
+    pub fn on_relese(&self, event: &xcb::x::KeyReleaseEvent) {
+        let mut input_buffer = self.input_buffer.borrow_mut();
+        if let Some(index) = input_buffer
+            .iter()
+            .position(|key| *key == KeyCode::from(event.detail()))
+        {
+            input_buffer.remove(index);
         }
     }
 }
@@ -81,6 +79,7 @@ impl From<u8> for KeyCode {
             140 => Self::SunFront,
             144 => Self::Find,
             146 => Self::Help,
+            _ => Self::Unknown(value),
         }
     }
 }
@@ -119,9 +118,16 @@ pub enum KeyCode {
     SunProps,
     Tab,
     Undo,
+    Unknown(u8),
     Up,
 }
 
+impl KeyCode {
+    pub fn is_modkey(&self) -> bool {
+        matches!(self, KeyCode::Modkey(_))
+    }
+}
+
 #[derive(PartialEq, Eq)]
 pub enum Letter {
     A,
@@ -152,8 +158,8 @@ pub enum Letter {
     Z,
 }
 
-impl ToString for Letter {
-    fn to_string(&self) -> String {
+impl Letter {
+    fn name(&self) -> String {
         match self {
             Letter::A => "a",
             Letter::B => "b",
@@ -186,6 +192,12 @@ impl ToString for Letter {
     }
 }
 
+impl Display for Letter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 impl TryFrom<u8> for Letter {
     fn try_from(value: u8) -> Result<Letter, Self::Error> {
         Ok(match value {
@@ -246,34 +258,39 @@ impl TryFrom<u8> for ModKey {
             108 => Self::Meta(KeyPosition::Right),
             133 | 206 => Self::Super(KeyPosition::Left),
             134 => Self::Super(KeyPosition::Right),
-            64 | 204 => Self::Alt(KeyPosition::Left),
-            108 => Self::Alt(KeyPosition::Right),
+            204 => Self::Alt(KeyPosition::Left),
             _ => return Err(()),
         })
     }
 }
 
-impl ToString for ModKey {
-    fn to_string(&self) -> String {
+impl ModKey {
+    fn name(&self) -> String {
         match self {
-            ModKey::Shift(side) => format!("{}_Sh", side.to_string()),
-            ModKey::Control(side) => format!("{}_C", side.to_string()),
-            ModKey::Alt(side) => format!("{}_A", side.to_string()),
-            ModKey::Super(side) => format!("{}_Sup", side.to_string()),
-            ModKey::Meta(side) => format!("{}_M", side.to_string()),
+            ModKey::Shift(side) => format!("{}_Sh", side),
+            ModKey::Control(side) => format!("{}_C", side),
+            ModKey::Alt(side) => format!("{}_A", side),
+            ModKey::Super(side) => format!("{}_Sup", side),
+            ModKey::Meta(side) => format!("{}_M", side),
             ModKey::Hyper => "H".to_string(),
         }
     }
 }
 
+impl Display for ModKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 #[derive(PartialEq, Eq)]
 pub enum KeyPosition {
     Left,
     Right,
 }
 
-impl ToString for KeyPosition {
-    fn to_string(&self) -> String {
+impl KeyPosition {
+    fn name(&self) -> String {
         match self {
             KeyPosition::Left => "L",
             KeyPosition::Right => "R",
@@ -282,6 +299,12 @@ impl ToString for KeyPosition {
     }
 }
 
+impl Display for KeyPosition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 #[derive(PartialEq, Eq)]
 pub enum Symbols {
     Apostrophe,
@@ -363,12 +386,12 @@ impl TryFrom<u8> for Numpad {
     type Error = ();
 }
 
-impl ToString for Numpad {
-    fn to_string(&self) -> String {
+impl Numpad {
+    fn name(&self) -> String {
         format!(
             "N_{}",
             match self {
-                Numpad::Num(num) => num.to_string().as_str(),
+                Numpad::Num(num) => return format!("N_{}", num),
                 Numpad::Devide => "/",
                 Numpad::Substract => "-",
                 Numpad::Add => "+",
@@ -383,6 +406,12 @@ impl ToString for Numpad {
     }
 }
 
+impl Display for Numpad {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 #[derive(PartialEq, Eq)]
 pub enum Functional {
     Audio(Audio),
@@ -394,7 +423,7 @@ pub enum Functional {
     Close,
     Copy,
     Cut,
-    DOS,
+    Dos,
     Display,
     DisplayOff,
     Documents,
@@ -406,7 +435,7 @@ pub enum Functional {
     Game,
     Go,
     HomePage,
-    KBD(KBD),
+    Kbd(Kbd),
     Kill,
     Launch(u8),
     LaunchA,
@@ -440,10 +469,10 @@ pub enum Functional {
     TaskPlane,
     Tools,
     TouchPad(TouchPad),
-    UWB,
-    WLAN,
-    WWAN,
-    WWW,
+    Uwb,
+    Wlan,
+    Wwan,
+    Www,
     WakeUp,
     WebCamera,
     Xref,
@@ -457,8 +486,8 @@ impl TryFrom<u8> for Functional {
             return Ok(Self::Audio(audio_key));
         }
 
-        if let Ok(kbd_key) = KBD::try_from(value) {
-            return Ok(Self::KBD(kbd_key));
+        if let Ok(kbd_key) = Kbd::try_from(value) {
+            return Ok(Self::Kbd(kbd_key));
         }
 
         if let Ok(monitor_key) = Monitor::try_from(value) {
@@ -471,12 +500,12 @@ impl TryFrom<u8> for Functional {
 
         Ok(match value {
             255 => Self::Kill,
-            254 => Self::WWAN,
+            254 => Self::Wwan,
             253 => Self::DisplayOff,
             250 => Self::PrevVMode,
             249 => Self::NextVMode,
-            247 => Self::UWB,
-            246 => Self::WLAN,
+            247 => Self::Uwb,
+            246 => Self::Wlan,
             245 => Self::Bluetooth,
             244 => Self::Battery,
             243 => Self::Documents,
@@ -514,11 +543,10 @@ impl TryFrom<u8> for Functional {
             162 => Self::TaskPlane,
             161 => Self::RotateWindows,
             160 => Self::ScreenSaver,
-            159 => Self::DOS,
-            158 => Self::WWW,
+            159 => Self::Dos,
+            158 => Self::Www,
             156..=157 => Self::Launch(1 + value - 156),
             155 => Self::Xref,
-            153 => Self::Send,
             152 => Self::Explorer,
             151 => Self::WakeUp,
             150 => Self::Sleep,
@@ -537,12 +565,12 @@ impl TryFrom<u8> for Functional {
     }
 }
 
-impl ToString for Functional {
-    fn to_string(&self) -> String {
+impl Functional {
+    fn name(&self) -> String {
         format!(
             "Fn_{}",
             match self {
-                Functional::Audio(audio) => audio.to_string().as_str(),
+                Functional::Audio(audio) => return format!("Fn_{}", audio),
                 Functional::Back => "Back",
                 Functional::Battery => "Battery",
                 Functional::Bluetooth => "Bluetooth",
@@ -551,7 +579,7 @@ impl ToString for Functional {
                 Functional::Close => "Close",
                 Functional::Copy => "Copy",
                 Functional::Cut => "Cut",
-                Functional::DOS => "DOS",
+                Functional::Dos => "DOS",
                 Functional::Display => "Display",
                 Functional::DisplayOff => "Display_Off",
                 Functional::Documents => "Documents",
@@ -563,7 +591,7 @@ impl ToString for Functional {
                 Functional::Game => "Game",
                 Functional::Go => "Go",
                 Functional::HomePage => "Home_Page",
-                Functional::KBD(kbd) => kbd.to_string().as_str(),
+                Functional::Kbd(kbd) => return format!("Fn_{}", kbd),
                 Functional::Kill => "Kill",
                 Functional::Launch(_) => "Launch",
                 Functional::LaunchA => "Launch_A",
@@ -572,7 +600,7 @@ impl ToString for Functional {
                 Functional::MailFarward => "Mail_Farward",
                 Functional::Menu => "Menu",
                 Functional::Messanger => "Messanger",
-                Functional::Monitor(monitor) => monitor.to_string().as_str(),
+                Functional::Monitor(monitor) => return format!("Fn_{}", monitor),
                 Functional::MyComputer => "My_Computer",
                 Functional::New => "New",
                 Functional::NextVMode => "Next_V_Mode",
@@ -596,11 +624,11 @@ impl ToString for Functional {
                 Functional::Suspend => "Suspend",
                 Functional::TaskPlane => "Task_Plane",
                 Functional::Tools => "Tools",
-                Functional::TouchPad(touchpad) => touchpad.to_string().as_str(),
-                Functional::UWB => "UWB",
-                Functional::WLAN => "WLAN",
-                Functional::WWAN => "WWAN",
-                Functional::WWW => "WW",
+                Functional::TouchPad(touchpad) => return format!("Fn_{}", touchpad),
+                Functional::Uwb => "UWB",
+                Functional::Wlan => "WLAN",
+                Functional::Wwan => "WWAN",
+                Functional::Www => "WW",
                 Functional::WakeUp => "Wake_Up",
                 Functional::WebCamera => "Web_Camera",
                 Functional::Xref => "Xref",
@@ -609,14 +637,20 @@ impl ToString for Functional {
     }
 }
 
+impl Display for Functional {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 #[derive(PartialEq, Eq)]
-pub enum KBD {
+pub enum Kbd {
     ToggleLight,
     BrightnessUp,
     BrightnessDown,
 }
 
-impl TryFrom<u8> for KBD {
+impl TryFrom<u8> for Kbd {
     type Error = ();
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
@@ -636,19 +670,25 @@ pub enum Monitor {
     BrightnessCycle,
 }
 
-impl ToString for KBD {
-    fn to_string(&self) -> String {
+impl Kbd {
+    fn name(&self) -> String {
         format!(
             "KDB_{}",
             match self {
-                KBD::ToggleLight => "ToggleLight",
-                KBD::BrightnessUp => "Brightness+",
-                KBD::BrightnessDown => "Brightness-",
+                Kbd::ToggleLight => "ToggleLight",
+                Kbd::BrightnessUp => "Brightness+",
+                Kbd::BrightnessDown => "Brightness-",
             }
         )
     }
 }
 
+impl Display for Kbd {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 impl TryFrom<u8> for Monitor {
     type Error = ();
 
@@ -662,8 +702,8 @@ impl TryFrom<u8> for Monitor {
     }
 }
 
-impl ToString for Monitor {
-    fn to_string(&self) -> String {
+impl Monitor {
+    fn name(&self) -> String {
         format!(
             "Mon_{}",
             match self {
@@ -675,6 +715,12 @@ impl ToString for Monitor {
     }
 }
 
+impl Display for Monitor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 #[derive(PartialEq, Eq)]
 pub enum TouchPad {
     On,
@@ -695,8 +741,8 @@ impl TryFrom<u8> for TouchPad {
     }
 }
 
-impl ToString for TouchPad {
-    fn to_string(&self) -> String {
+impl TouchPad {
+    fn name(&self) -> String {
         format!(
             "TouchPad_{}",
             match self {
@@ -708,6 +754,12 @@ impl ToString for TouchPad {
     }
 }
 
+impl Display for TouchPad {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 #[derive(PartialEq, Eq)]
 pub enum Audio {
     Volume(AudioVolume),
@@ -748,12 +800,12 @@ impl TryFrom<u8> for Audio {
     }
 }
 
-impl ToString for Audio {
-    fn to_string(&self) -> String {
+impl Audio {
+    fn name(&self) -> String {
         format!(
             "Audio_{}",
             match self {
-                Audio::Volume(volume) => volume.to_string().as_str(),
+                Audio::Volume(volume) => return format!("Audio_{}", volume),
                 Audio::Play => "Play",
                 Audio::Next => "Next",
                 Audio::Prev => "Prev",
@@ -770,6 +822,12 @@ impl ToString for Audio {
     }
 }
 
+impl Display for Audio {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 #[derive(PartialEq, Eq)]
 pub enum AudioVolume {
     Lower,
@@ -777,8 +835,8 @@ pub enum AudioVolume {
     Mute,
 }
 
-impl ToString for AudioVolume {
-    fn to_string(&self) -> String {
+impl AudioVolume {
+    fn name(&self) -> String {
         format!(
             "Vol{}",
             match self {
@@ -790,6 +848,12 @@ impl ToString for AudioVolume {
     }
 }
 
+impl Display for AudioVolume {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 impl TryFrom<u8> for AudioVolume {
     type Error = ();
 
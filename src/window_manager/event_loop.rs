@@ -0,0 +1,103 @@
+use std::{
+    collections::HashSet,
+    io,
+    os::fd::RawFd,
+    time::{Duration, Instant},
+};
+
+use xcb::{x, Event};
+
+/// Blocks until the X connection or one of the IPC sockets is readable or
+/// the timeout runs out. Returns whether an IPC socket is ready.
+pub fn wait(x_connection: RawFd, ipc: &[RawFd], timeout: Option<Duration>) -> io::Result<bool> {
+    let mut fds: Vec<libc::pollfd> = std::iter::once(x_connection)
+        .chain(ipc.iter().copied())
+        .map(|fd| libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        })
+        .collect();
+    let timeout = match timeout {
+        Some(timeout) => timeout.as_millis().min(i32::MAX as u128) as i32,
+        None => -1,
+    };
+    let result = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout) };
+    if result < 0 {
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+    Ok(fds[1..].iter().any(|fd| fd.revents != 0))
+}
+
+#[derive(PartialEq, Eq, Hash)]
+enum CoalescingKey {
+    Configure(x::Window),
+    Motion(x::Window),
+}
+
+impl CoalescingKey {
+    fn of(event: &Event) -> Option<CoalescingKey> {
+        match event {
+            Event::X(x::Event::ConfigureNotify(event)) => {
+                Some(CoalescingKey::Configure(event.window()))
+            }
+            Event::X(x::Event::MotionNotify(event)) => Some(CoalescingKey::Motion(event.event())),
+            _ => None,
+        }
+    }
+}
+
+/// Drops every ConfigureNotify and MotionNotify that is followed by a newer
+/// one for the same window, as long as no other event lies in between.
+/// Order of the remaining events is kept.
+pub fn coalesce(events: Vec<Event>) -> Vec<Event> {
+    let mut seen = HashSet::new();
+    let mut coalesced: Vec<Event> = events
+        .into_iter()
+        .rev()
+        .filter(|event| match CoalescingKey::of(event) {
+            Some(key) => seen.insert(key),
+            None => {
+                seen.clear();
+                true
+            }
+        })
+        .collect();
+    coalesced.reverse();
+    coalesced
+}
+
+pub struct Timers<T> {
+    timers: Vec<(Instant, T)>,
+}
+
+impl<T> Timers<T> {
+    pub fn new() -> Timers<T> {
+        Timers { timers: Vec::new() }
+    }
+
+    pub fn schedule(&mut self, delay: Duration, timer: T) {
+        self.timers.push((Instant::now() + delay, timer));
+    }
+
+    /// Time left until the closest timer expires.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timers
+            .iter()
+            .map(|(deadline, _)| deadline.saturating_duration_since(Instant::now()))
+            .min()
+    }
+
+    pub fn expired(&mut self) -> Vec<T> {
+        let now = Instant::now();
+        let (expired, pending) = self
+            .timers
+            .drain(..)
+            .partition(|(deadline, _)| *deadline <= now);
+        self.timers = pending;
+        expired.into_iter().map(|(_, timer)| timer).collect()
+    }
+}
@@ -0,0 +1,219 @@
+use std::sync::Arc;
+
+use xcb::{x, Connection};
+
+use crate::{
+    atoms::Atoms,
+    window::{
+        ewmh::{NetWmState, WindowType},
+        WindowGeometry,
+    },
+};
+
+/// Root window properties read by panels, pagers and tools like xdotool.
+/// Desktops are tags, desktop `n` is tag `n + 1`.
+pub struct Ewmh {
+    connection: Arc<Connection>,
+    atoms: Arc<Atoms>,
+    root: x::Window,
+    check_window: x::Window,
+}
+
+impl Ewmh {
+    pub fn new(connection: Arc<Connection>, atoms: Arc<Atoms>, root: x::Window) -> Ewmh {
+        let check_window = connection.generate_id();
+        connection.send_request(&x::CreateWindow {
+            depth: 0,
+            wid: check_window,
+            parent: root,
+            x: -1,
+            y: -1,
+            width: 1,
+            height: 1,
+            border_width: 0,
+            class: x::WindowClass::InputOnly,
+            visual: 0,
+            value_list: &[],
+        });
+        let ewmh = Ewmh {
+            connection,
+            atoms,
+            root,
+            check_window,
+        };
+        for window in [root, check_window] {
+            ewmh.set_property(
+                window,
+                ewmh.atoms.net_supporting_wm_check,
+                x::ATOM_WINDOW,
+                &[check_window],
+            );
+        }
+        ewmh.set_property(
+            check_window,
+            ewmh.atoms.net_wm_name,
+            ewmh.atoms.utf8_string,
+            b"xrwm",
+        );
+        ewmh.set_property(
+            root,
+            ewmh.atoms.net_supported,
+            x::ATOM_ATOM,
+            &ewmh.supported(),
+        );
+        ewmh
+    }
+
+    fn supported(&self) -> Vec<x::Atom> {
+        let states = NetWmState::ALL.map(|state| state.atom(&self.atoms));
+        let mut supported = vec![
+            self.atoms.net_supported,
+            self.atoms.net_supporting_wm_check,
+            self.atoms.net_wm_name,
+            self.atoms.net_client_list,
+            self.atoms.net_client_list_stacking,
+            self.atoms.net_active_window,
+            self.atoms.net_number_of_desktops,
+            self.atoms.net_current_desktop,
+            self.atoms.net_desktop_names,
+            self.atoms.net_wm_desktop,
+            self.atoms.net_close_window,
+            self.atoms.net_wm_state,
+            self.atoms.net_wm_window_type,
+            self.atoms.net_wm_strut,
+            self.atoms.net_wm_strut_partial,
+            self.atoms.net_workarea,
+        ];
+        supported.extend(states);
+        supported.extend(WindowType::atoms(&self.atoms));
+        supported
+    }
+
+    pub fn set_client_list(&self, windows: &[x::Window]) {
+        self.set_property(
+            self.root,
+            self.atoms.net_client_list,
+            x::ATOM_WINDOW,
+            windows,
+        );
+    }
+
+    /// Windows in bottom-to-top stacking order.
+    pub fn set_client_list_stacking(&self, windows: &[x::Window]) {
+        self.set_property(
+            self.root,
+            self.atoms.net_client_list_stacking,
+            x::ATOM_WINDOW,
+            windows,
+        );
+    }
+
+    pub fn set_active_window(&self, window: Option<x::Window>) {
+        self.set_property(
+            self.root,
+            self.atoms.net_active_window,
+            x::ATOM_WINDOW,
+            &[window.unwrap_or(x::WINDOW_NONE)],
+        );
+    }
+
+    pub fn set_desktops(&self, names: &[String]) {
+        self.set_property(
+            self.root,
+            self.atoms.net_number_of_desktops,
+            x::ATOM_CARDINAL,
+            &[names.len() as u32],
+        );
+        let names: Vec<u8> = names
+            .iter()
+            .flat_map(|name| name.bytes().chain([0]))
+            .collect();
+        self.set_property(
+            self.root,
+            self.atoms.net_desktop_names,
+            self.atoms.utf8_string,
+            &names,
+        );
+    }
+
+    /// The same usable area for every desktop.
+    pub fn set_workarea(&self, area: WindowGeometry, desktops: usize) {
+        let area = [
+            area.x() as u32,
+            area.y() as u32,
+            area.width() as u32,
+            area.height() as u32,
+        ];
+        self.set_property(
+            self.root,
+            self.atoms.net_workarea,
+            x::ATOM_CARDINAL,
+            &area.repeat(desktops),
+        );
+    }
+
+    pub fn set_current_desktop(&self, tag: u32) {
+        self.set_property(
+            self.root,
+            self.atoms.net_current_desktop,
+            x::ATOM_CARDINAL,
+            &[tag_to_desktop(tag)],
+        );
+    }
+
+    /// `_XRWM_LAYOUT_NAME`, for bars that show the layout of the current tag.
+    pub fn set_layout_name(&self, name: &str) {
+        self.set_property(
+            self.root,
+            self.atoms.xrwm_layout_name,
+            self.atoms.utf8_string,
+            name.as_bytes(),
+        );
+    }
+
+    /// `_XRWM_LAYOUT_NAMES`, the layout of every tag in tag order.
+    pub fn set_layout_names(&self, names: &[String]) {
+        let names: Vec<u8> = names
+            .iter()
+            .flat_map(|name| name.bytes().chain([0]))
+            .collect();
+        self.set_property(
+            self.root,
+            self.atoms.xrwm_layout_names,
+            self.atoms.utf8_string,
+            &names,
+        );
+    }
+
+    fn set_property<P: x::PropEl>(
+        &self,
+        window: x::Window,
+        property: x::Atom,
+        r#type: x::Atom,
+        data: &[P],
+    ) {
+        self.connection.send_request(&x::ChangeProperty {
+            mode: x::PropMode::Replace,
+            window,
+            property,
+            r#type,
+            data,
+        });
+    }
+}
+
+impl Drop for Ewmh {
+    fn drop(&mut self) {
+        self.connection.send_request(&x::DestroyWindow {
+            window: self.check_window,
+        });
+    }
+}
+
+pub fn tag_to_desktop(tag: u32) -> u32 {
+    tag.saturating_sub(1)
+}
+
+pub fn desktop_to_tag(desktop: u32) -> u32 {
+    desktop.saturating_add(1)
+}
@@ -0,0 +1,33 @@
+use super::compositor::{Client, Compositor, Placement};
+use crate::window::WindowGeometry;
+
+/// Windows keep the geometry they asked for, or were moved and resized to.
+pub struct FloatingCompositor {
+    border_size: u16,
+}
+
+impl FloatingCompositor {
+    pub fn new(border_size: u16) -> FloatingCompositor {
+        FloatingCompositor { border_size }
+    }
+}
+
+impl Compositor for FloatingCompositor {
+    fn locate_windows(&mut self, area: WindowGeometry, clients: &[Client]) -> Vec<Placement> {
+        clients
+            .iter()
+            .map(|client| {
+                let geometry = client.floating_geometry().unwrap_or(area);
+                Placement::new(client.window(), geometry, self.border_size)
+            })
+            .collect()
+    }
+
+    fn name(&self, _focused: Option<usize>, _count: usize) -> String {
+        "><>".to_string()
+    }
+
+    fn is_floating(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,298 @@
+use xcb::{x, Cookie};
+
+use super::{
+    action::Cycle,
+    focus_history::{FocusCycle, Tag},
+    RuntimeError, WindowManager,
+};
+use crate::{
+    config::{FocusMode, FocusStealing},
+    window::{ewmh::NetWmState, icccm::InputModel},
+};
+
+/// Time of events that carry the server time.
+pub(super) fn event_time(event: &x::Event) -> Option<x::Timestamp> {
+    match event {
+        x::Event::KeyPress(event) => Some(event.time()),
+        x::Event::KeyRelease(event) => Some(event.time()),
+        x::Event::ButtonPress(event) => Some(event.time()),
+        x::Event::ButtonRelease(event) => Some(event.time()),
+        x::Event::MotionNotify(event) => Some(event.time()),
+        x::Event::EnterNotify(event) => Some(event.time()),
+        x::Event::LeaveNotify(event) => Some(event.time()),
+        x::Event::PropertyNotify(event) => Some(event.time()),
+        _ => None,
+    }
+}
+
+impl WindowManager {
+    /// Focuses the window as its input model asks for, or the root window for
+    /// `None`. Windows that take no input never get the focus.
+    pub(super) fn focus(&mut self, window: Option<x::Window>) -> Result<(), RuntimeError> {
+        let Some(managed) = window.and_then(|window| self.find_window(window)) else {
+            if let Some(previous) = self.focused {
+                self.grab_buttons(previous, false);
+            }
+            self.set_input_focus(self.screen.root());
+            self.focused = None;
+            self.ewmh.set_active_window(None);
+            return Ok(());
+        };
+        let window = managed.window();
+        let input_model = managed.input_model();
+        if input_model == InputModel::NoInput {
+            return Ok(());
+        }
+        if let Some(previous) = self.focused.filter(|previous| *previous != window) {
+            self.grab_buttons(previous, false);
+        }
+        self.grab_buttons(window, true);
+        if input_model.sets_focus() {
+            self.set_input_focus(window);
+        }
+        let managed = self.find_window(window).unwrap();
+        if input_model.takes_focus() {
+            managed.take_focus(self.event_time)?;
+        }
+        managed.set_state(NetWmState::DemandsAttention, false);
+        let previous = self.focused;
+        let monitor = self.monitor_of(managed);
+        if let Some(monitor) = monitor.filter(|monitor| *monitor != self.screen.focused()) {
+            self.focus_monitor(monitor);
+        }
+        if self.focus_cycle.is_none() {
+            self.focus_history.touch(self.history_tag(), window);
+        }
+        self.focused = Some(window);
+        self.ewmh.set_active_window(self.focused);
+        // Tiles only overlap in layouts like monocle, where the focused one
+        // has to be seen.
+        let tiled = self.layout.iter().any(|(tiled, _)| *tiled == window);
+        if tiled {
+            self.raise(window);
+        }
+        if tiled && previous != Some(window) && self.tag_layout().compositor().follows_focus() {
+            return self.arrange();
+        }
+        self.update_layout_name();
+        self.draw_title_bars();
+        Ok(())
+    }
+
+    /// Focuses the window at the time of the last event. FocusIn events of
+    /// earlier focus changes are told apart by their sequence number.
+    fn set_input_focus(&mut self, window: x::Window) {
+        let cookie = self.x_server_connecton.send_request(&x::SetInputFocus {
+            revert_to: x::InputFocus::PointerRoot,
+            focus: window,
+            time: self.event_time,
+        });
+        self.focus_sequence = cookie.sequence() as u16;
+    }
+
+    /// Remembers the last request of a layout change, crossing events caused
+    /// by moving windows under the pointer are older and get ignored.
+    pub(super) fn ignore_layout_crossings(&mut self) {
+        let cookie = self.x_server_connecton.send_request(&x::NoOperation {});
+        self.layout_sequence = cookie.sequence() as u16;
+    }
+
+    pub(super) fn on_enter_notify(
+        &mut self,
+        event: x::EnterNotifyEvent,
+    ) -> Result<(), RuntimeError> {
+        let caused_by_layout = (self.layout_sequence.wrapping_sub(event.sequence()) as i16) > 0;
+        if self.config.focus_config().focus_mode() == FocusMode::Click
+            || caused_by_layout
+            || event.mode() != x::NotifyMode::Normal
+            || event.detail() == x::NotifyDetail::Inferior
+        {
+            return Ok(());
+        }
+        let window = event.event();
+        if Some(window) == self.focused
+            || self
+                .find_window(window)
+                .and_then(|window| self.monitor_of(window))
+                .is_none()
+        {
+            return Ok(());
+        }
+        self.focus(Some(window))
+    }
+
+    /// In strict mode, leaving the focused window to the root window
+    /// unfocuses it.
+    pub(super) fn on_leave_notify(
+        &mut self,
+        event: x::LeaveNotifyEvent,
+    ) -> Result<(), RuntimeError> {
+        let caused_by_layout = (self.layout_sequence.wrapping_sub(event.sequence()) as i16) > 0;
+        if self.config.focus_config().focus_mode() != FocusMode::StrictFollowsMouse
+            || caused_by_layout
+            || event.mode() != x::NotifyMode::Normal
+            || event.detail() != x::NotifyDetail::Ancestor
+            || Some(event.event()) != self.focused
+        {
+            return Ok(());
+        }
+        self.focus(None)
+    }
+
+    /// Keeps the focus if the focused window is still visible, otherwise
+    /// focuses the window of the current tag that was focused last.
+    pub(super) fn refocus(&mut self) -> Result<(), RuntimeError> {
+        if self.focused.is_some_and(|window| self.is_focusable(window)) {
+            return Ok(());
+        }
+        let next = self.focus_order().first().copied();
+        self.focus(next)
+    }
+
+    /// Tag of the focused monitor in the focus history.
+    fn history_tag(&self) -> Tag {
+        (self.tag_set(self.screen.focused()), self.current_tag())
+    }
+
+    /// Focusable windows of the focused monitor, most recently focused
+    /// first, followed by the never focused ones from top to bottom.
+    pub(super) fn focus_order(&self) -> Vec<x::Window> {
+        let history = self.focus_history.windows(&self.history_tag());
+        let mut windows: Vec<x::Window> = history.to_vec();
+        windows.extend(
+            self.stacking
+                .iter()
+                .rev()
+                .filter(|window| !history.contains(window)),
+        );
+        windows.retain(|window| {
+            self.is_focusable(*window)
+                && self
+                    .find_window(*window)
+                    .is_some_and(|window| self.monitor_of(window) == Some(self.screen.focused()))
+        });
+        windows
+    }
+
+    fn is_focusable(&self, window: x::Window) -> bool {
+        self.find_window(window).is_some_and(|window| {
+            self.monitor_of(window).is_some() && window.input_model() != InputModel::NoInput
+        })
+    }
+
+    pub(super) fn focus_last(&mut self) -> Result<(), RuntimeError> {
+        let last = self
+            .focus_order()
+            .into_iter()
+            .find(|window| Some(*window) != self.focused);
+        match last {
+            Some(window) => self.activate_window(window),
+            None => Ok(()),
+        }
+    }
+
+    /// Starts a cycle, or steps further, through the windows in focus order.
+    /// The keyboard is grabbed until the modifier is released; if none is
+    /// held, e.g. for IPC commands, the step is committed right away.
+    pub(super) fn cycle_focus(&mut self, cycle: Cycle) -> Result<(), RuntimeError> {
+        if self.focus_cycle.is_none() {
+            self.focus_cycle = Some(FocusCycle::new(self.focus_order()));
+            self.x_server_connecton.send_request(&x::GrabKeyboard {
+                owner_events: false,
+                grab_window: self.screen.root(),
+                time: self.event_time,
+                pointer_mode: x::GrabMode::Async,
+                keyboard_mode: x::GrabMode::Async,
+            });
+        }
+        let next = self
+            .focus_cycle
+            .as_mut()
+            .and_then(|focus_cycle| focus_cycle.step(cycle == Cycle::Next));
+        if let Some(window) = next {
+            self.raise(window);
+            self.focus(Some(window))?;
+        }
+        let cookie = self.x_server_connecton.send_request(&x::QueryPointer {
+            window: self.screen.root(),
+        });
+        let modifiers = x::KeyButMask::SHIFT
+            | x::KeyButMask::CONTROL
+            | x::KeyButMask::MOD1
+            | x::KeyButMask::MOD4;
+        if !self
+            .x_server_connecton
+            .wait_for_reply(cookie)?
+            .mask()
+            .intersects(modifiers)
+        {
+            self.commit_focus_cycle();
+        }
+        Ok(())
+    }
+
+    /// Ends the cycle, the selected window becomes the most recent one.
+    pub(super) fn commit_focus_cycle(&mut self) {
+        let Some(focus_cycle) = self.focus_cycle.take() else {
+            return;
+        };
+        self.x_server_connecton.send_request(&x::UngrabKeyboard {
+            time: self.event_time,
+        });
+        if let Some(window) = focus_cycle.selected() {
+            self.focus_history.touch(self.history_tag(), window);
+        }
+    }
+
+    pub(super) fn on_key_press(&mut self, event: x::KeyPressEvent) -> Result<(), RuntimeError> {
+        self.event_handler.on_key_press(&event);
+        Ok(())
+    }
+
+    pub(super) fn on_key_release(&mut self, event: x::KeyReleaseEvent) -> Result<(), RuntimeError> {
+        if self.event_handler.on_key_release(&event) {
+            self.commit_focus_cycle();
+        }
+        Ok(())
+    }
+
+    /// A client focused one of its windows by itself. The focus is given back
+    /// unless the focus stealing policy allows it.
+    pub(super) fn on_focus_in(&mut self, event: x::FocusInEvent) -> Result<(), RuntimeError> {
+        if matches!(event.mode(), x::NotifyMode::Grab | x::NotifyMode::Ungrab)
+            || matches!(
+                event.detail(),
+                x::NotifyDetail::Pointer | x::NotifyDetail::Inferior
+            )
+        {
+            return Ok(());
+        }
+        let window = event.event();
+        // Events of an earlier SetInputFocus of the window manager itself
+        // arrive after the focus moved on.
+        let caused_by_focus_change =
+            (self.focus_sequence.wrapping_sub(event.sequence()) as i16) > 0;
+        if Some(window) == self.focused || !self.is_managed(window) || caused_by_focus_change {
+            return Ok(());
+        }
+        match self.allows_focus_stealing(window) {
+            true => self.focus(Some(window)),
+            false => self.focus(self.focused),
+        }
+    }
+
+    /// Applies `focus_stealing` to a window that wants the focus without the
+    /// user asking for it.
+    pub(super) fn allows_focus_stealing(&self, window: x::Window) -> bool {
+        match self.config.focus_config().focus_stealing() {
+            FocusStealing::Allow => true,
+            FocusStealing::Deny => false,
+            FocusStealing::Attention => {
+                if let Some(window) = self.find_window(window) {
+                    window.set_state(NetWmState::DemandsAttention, true);
+                }
+                false
+            }
+        }
+    }
+}
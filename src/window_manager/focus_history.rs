@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+use xcb::x;
+
+/// A tag of a tag set, the tag set is `None` when the monitors share the
+/// tags.
+pub type Tag = (Option<String>, u32);
+
+/// Windows of every tag in the order they were focused, the most recent
+/// first.
+#[derive(Default)]
+pub struct FocusHistory {
+    tags: HashMap<Tag, Vec<x::Window>>,
+}
+
+impl FocusHistory {
+    pub fn touch(&mut self, tag: Tag, window: x::Window) {
+        let windows = self.tags.entry(tag).or_default();
+        windows.retain(|focused| *focused != window);
+        windows.insert(0, window);
+    }
+
+    pub fn remove(&mut self, window: x::Window) {
+        for windows in self.tags.values_mut() {
+            windows.retain(|focused| *focused != window);
+        }
+    }
+
+    pub fn windows(&self, tag: &Tag) -> &[x::Window] {
+        self.tags.get(tag).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Alt-tab style stepping through the history, the history is only updated
+/// once the cycle is committed.
+pub struct FocusCycle {
+    windows: Vec<x::Window>,
+    index: usize,
+}
+
+impl FocusCycle {
+    pub fn new(windows: Vec<x::Window>) -> FocusCycle {
+        FocusCycle { windows, index: 0 }
+    }
+
+    pub fn step(&mut self, forward: bool) -> Option<x::Window> {
+        if self.windows.is_empty() {
+            return None;
+        }
+        let length = self.windows.len();
+        self.index = match forward {
+            true => (self.index + 1) % length,
+            false => (self.index + length - 1) % length,
+        };
+        Some(self.windows[self.index])
+    }
+
+    pub fn selected(&self) -> Option<x::Window> {
+        self.windows.get(self.index).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_manager::compositor::tests::window;
+
+    #[test]
+    fn tag_sets_have_their_own_history() {
+        let mut history = FocusHistory::default();
+        let left = (Some("left".to_string()), 1);
+        let right = (Some("right".to_string()), 1);
+        history.touch(left.clone(), window(1));
+        history.touch(right.clone(), window(2));
+        history.touch(left.clone(), window(3));
+        assert_eq!(history.windows(&left), [window(3), window(1)]);
+        assert_eq!(history.windows(&right), [window(2)]);
+        history.remove(window(3));
+        assert_eq!(history.windows(&left), [window(1)]);
+        assert!(history.windows(&(None, 1)).is_empty());
+    }
+}
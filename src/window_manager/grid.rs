@@ -0,0 +1,120 @@
+use xcb::x;
+
+use super::{
+    compositor::{Client, Compositor, LayoutMessage, Placement},
+    tiling::{change_gap, place, shrink, split, tiled},
+};
+use crate::{config::TilingConfig, window::WindowGeometry};
+
+/// Windows share the area evenly in columns, the columns on the right get
+/// the extra windows when the count isn't a square.
+pub struct GridCompositor {
+    border_size: u16,
+    inner_gap: u16,
+    outer_gap: u16,
+    smart_borders: bool,
+    smart_gaps: bool,
+}
+
+impl GridCompositor {
+    pub fn new(tiling_config: TilingConfig, border_size: u16) -> GridCompositor {
+        let gaps_config = tiling_config.gaps_config();
+        GridCompositor {
+            border_size,
+            inner_gap: gaps_config.as_ref().map_or(0, |gaps| gaps.inner_size()),
+            outer_gap: gaps_config.as_ref().map_or(0, |gaps| gaps.outer_size()),
+            smart_borders: tiling_config.smart_borders(),
+            smart_gaps: tiling_config.smart_gaps(),
+        }
+    }
+}
+
+/// Cells of `count` windows, column by column from the left.
+pub fn grid(area: WindowGeometry, count: usize, gap: u16) -> Vec<WindowGeometry> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let columns = (1..=count)
+        .find(|columns| columns * columns >= count)
+        .unwrap_or(count);
+    split(area, columns, false, gap)
+        .into_iter()
+        .enumerate()
+        .flat_map(|(column, column_area)| {
+            let rows = count / columns + usize::from(column >= columns - count % columns);
+            split(column_area, rows, true, gap)
+        })
+        .collect()
+}
+
+impl Compositor for GridCompositor {
+    fn locate_windows(&mut self, area: WindowGeometry, clients: &[Client]) -> Vec<Placement> {
+        let clients = tiled(clients);
+        let area = match clients.len() == 1 && self.smart_gaps {
+            true => area,
+            false => shrink(area, self.outer_gap),
+        };
+        let cells = grid(area, clients.len(), self.inner_gap);
+        place(&clients, cells, self.border_size, self.smart_borders)
+    }
+
+    fn name(&self, _focused: Option<usize>, _count: usize) -> String {
+        "###".to_string()
+    }
+
+    fn handle_message(&mut self, message: LayoutMessage, _focused: Option<x::Window>) {
+        if let LayoutMessage::ChangeGaps(delta) = message {
+            self.inner_gap = change_gap(self.inner_gap, delta);
+            self.outer_gap = change_gap(self.outer_gap, delta);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_manager::compositor::tests::cells;
+
+    #[test]
+    fn extra_windows_go_to_the_right_columns() {
+        let area = WindowGeometry::new(0, 0, 1200, 600);
+        assert_eq!(grid(area, 0, 0), Vec::new());
+        assert_eq!(grid(area, 1, 0), vec![area]);
+        assert_eq!(
+            grid(area, 2, 0),
+            cells(&[(0, 0, 600, 600), (600, 0, 600, 600)])
+        );
+        assert_eq!(
+            grid(area, 3, 0),
+            cells(&[(0, 0, 600, 600), (600, 0, 600, 300), (600, 300, 600, 300)])
+        );
+        assert_eq!(
+            grid(area, 4, 0),
+            cells(&[
+                (0, 0, 600, 300),
+                (0, 300, 600, 300),
+                (600, 0, 600, 300),
+                (600, 300, 600, 300),
+            ])
+        );
+        assert_eq!(
+            grid(area, 5, 0),
+            cells(&[
+                (0, 0, 400, 600),
+                (400, 0, 400, 300),
+                (400, 300, 400, 300),
+                (800, 0, 400, 300),
+                (800, 300, 400, 300),
+            ])
+        );
+    }
+
+    #[test]
+    fn gaps_separate_rows_and_columns() {
+        let area = WindowGeometry::new(0, 0, 1210, 610);
+        assert_eq!(
+            grid(area, 3, 10),
+            cells(&[(0, 0, 600, 610), (610, 0, 600, 300), (610, 310, 600, 300)])
+        );
+    }
+}
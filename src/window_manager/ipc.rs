@@ -0,0 +1,204 @@
+use std::{
+    fs::{self, DirBuilder},
+    io::{self, BufRead, BufReader, ErrorKind, Read, Write},
+    os::{
+        fd::{AsRawFd, RawFd},
+        unix::{
+            fs::{DirBuilderExt, MetadataExt},
+            net::{UnixListener, UnixStream},
+        },
+    },
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+/// Connections that haven't sent a whole command by then are dropped.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_COMMAND_LENGTH: usize = 4096;
+
+/// Unix socket that accepts one command per connection. The command is a
+/// single line (see `Action`), the answer is `ok` or `error: <reason>`.
+/// Connections are read without blocking, as their data arrives.
+pub struct IpcServer {
+    listener: UnixListener,
+    path: PathBuf,
+    clients: Vec<IpcClient>,
+}
+
+/// Connection whose command hasn't fully arrived yet.
+struct IpcClient {
+    stream: UnixStream,
+    buffer: Vec<u8>,
+    accepted: Instant,
+}
+
+pub struct IpcRequest {
+    stream: UnixStream,
+    command: String,
+}
+
+/// `$XDG_RUNTIME_DIR`, or a directory in `/tmp` that only the user can
+/// access.
+pub fn runtime_dir() -> io::Result<PathBuf> {
+    if let Ok(directory) = std::env::var("XDG_RUNTIME_DIR") {
+        return Ok(PathBuf::from(directory));
+    }
+    let uid = unsafe { libc::getuid() };
+    let directory = PathBuf::from(format!("/tmp/xrwm-{}", uid));
+    if let Err(error) = DirBuilder::new().mode(0o700).create(&directory) {
+        if error.kind() != ErrorKind::AlreadyExists {
+            return Err(error);
+        }
+    }
+    // Another user could have created it first.
+    let metadata = fs::symlink_metadata(&directory)?;
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+        return Err(io::Error::new(
+            ErrorKind::PermissionDenied,
+            format!("{} is not a private directory", directory.display()),
+        ));
+    }
+    Ok(directory)
+}
+
+pub fn socket_path() -> io::Result<PathBuf> {
+    if let Ok(path) = std::env::var("XRWM_SOCKET") {
+        return Ok(PathBuf::from(path));
+    }
+    let display = std::env::var("DISPLAY")
+        .unwrap_or_default()
+        .replace('/', "_");
+    Ok(runtime_dir()?.join(format!("xrwm{}.sock", display)))
+}
+
+pub fn send_command(command: &str) -> io::Result<String> {
+    let mut stream = UnixStream::connect(socket_path()?)?;
+    writeln!(stream, "{}", command)?;
+    let mut answer = String::new();
+    BufReader::new(stream).read_line(&mut answer)?;
+    Ok(answer.trim_end().to_string())
+}
+
+impl IpcServer {
+    pub fn bind() -> io::Result<IpcServer> {
+        let path = socket_path()?;
+        if UnixStream::connect(&path).is_err() {
+            let _ = fs::remove_file(&path);
+        }
+        let listener = UnixListener::bind(&path)?;
+        listener.set_nonblocking(true)?;
+        Ok(IpcServer {
+            listener,
+            path,
+            clients: Vec::new(),
+        })
+    }
+
+    /// The listening socket and the connections that are still being read,
+    /// to be polled.
+    pub fn fds(&self) -> Vec<RawFd> {
+        let mut fds = vec![self.listener.as_raw_fd()];
+        fds.extend(self.clients.iter().map(|client| client.stream.as_raw_fd()));
+        fds
+    }
+
+    /// Accepts new connections and reads what arrived on every connection.
+    /// Returns the commands that are complete.
+    pub fn accept(&mut self) -> Vec<IpcRequest> {
+        while let Ok((stream, _)) = self.listener.accept() {
+            if stream.set_nonblocking(true).is_ok() {
+                self.clients.push(IpcClient {
+                    stream,
+                    buffer: Vec::new(),
+                    accepted: Instant::now(),
+                });
+            }
+        }
+        let mut requests = Vec::new();
+        let mut pending = Vec::new();
+        for mut client in self.clients.drain(..) {
+            match client.read() {
+                Ok(true) => requests.push(client.into_request()),
+                Ok(false) if client.accepted.elapsed() < READ_TIMEOUT => pending.push(client),
+                _ => {}
+            }
+        }
+        self.clients = pending;
+        requests
+    }
+
+    /// Time left until the oldest connection times out.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.clients
+            .iter()
+            .map(|client| {
+                (client.accepted + READ_TIMEOUT).saturating_duration_since(Instant::now())
+            })
+            .min()
+    }
+
+    /// Drops the connections that didn't send their command in time, even
+    /// when nothing arrives on the sockets.
+    pub fn drop_expired(&mut self) {
+        self.clients
+            .retain(|client| client.accepted.elapsed() < READ_TIMEOUT);
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl IpcClient {
+    /// Reads what is available, returns whether the command is complete.
+    /// The end of the stream also ends the command.
+    fn read(&mut self) -> io::Result<bool> {
+        let mut chunk = [0; 512];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Ok(true),
+                Ok(length) => {
+                    self.buffer.extend_from_slice(&chunk[..length]);
+                    if self.buffer.contains(&b'\n') {
+                        return Ok(true);
+                    }
+                    if self.buffer.len() > MAX_COMMAND_LENGTH {
+                        return Err(io::Error::new(ErrorKind::InvalidData, "command too long"));
+                    }
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => return Ok(false),
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    fn into_request(self) -> IpcRequest {
+        let line = self
+            .buffer
+            .split(|byte| *byte == b'\n')
+            .next()
+            .unwrap_or(&[]);
+        IpcRequest {
+            stream: self.stream,
+            command: String::from_utf8_lossy(line).trim().to_string(),
+        }
+    }
+}
+
+impl IpcRequest {
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// Written without blocking, the answer fits the socket buffer of a
+    /// new connection.
+    pub fn reply(mut self, result: Result<(), String>) {
+        let _ = match result {
+            Ok(()) => writeln!(self.stream, "ok"),
+            Err(reason) => writeln!(self.stream, "error: {}", reason),
+        };
+    }
+}
@@ -1,120 +1,1292 @@
-use std::{cell::RefCell, sync::Arc, thread};
+use std::{collections::HashMap, fmt::Display, os::fd::AsRawFd, sync::Arc};
 
-use crate::config::Config;
-use xcb::{Connection, Event, UnknownEvent};
+use crate::{
+    atoms::Atoms,
+    config::{Config, LayoutKind, MonitorTags},
+    screen::Screen,
+    window::{
+        ewmh::{NetWmState, Strut, WindowType, ALL_DESKTOPS},
+        TagRules, Window, WindowGeometry, WindowPosition, WindowSize, XWindow,
+    },
+};
+use xcb::{x, Connection, Event, UnknownEvent, Xid, XidNew};
 
-use self::tiling::TilingCompositor;
+use self::{
+    action::{Action, Cycle},
+    compositor::{Client, Compositor},
+    direction::Direction,
+    event_handler::EventHandler,
+    event_loop::Timers,
+    ewmh::Ewmh,
+    floating::FloatingCompositor,
+    focus_history::{FocusCycle, FocusHistory},
+    ipc::{IpcRequest, IpcServer},
+    mouse::Drag,
+    restart::{LayoutState, State, WindowState},
+    tag_layout::{tag_layouts, TagLayout},
+    title_bars::TitleBars,
+};
 
+pub mod action;
+mod bsp;
+mod centered_master;
+mod compositor;
+mod containers;
+pub mod direction;
 mod event_handler;
+mod event_loop;
+pub mod ewmh;
 mod floating;
+mod focus;
+mod focus_history;
+mod grid;
+pub mod ipc;
+mod monitors;
 mod monocle;
+mod mouse;
+pub mod restart;
+mod scrolling;
+mod spiral;
+mod stacking;
+mod tag_layout;
 mod tiling;
+mod title_bars;
 
-pub type RuntimeError = xcb::Error;
+pub enum RuntimeError {
+    XError(xcb::Error),
+    IoError(std::io::Error),
+    AnotherWindowManagerRunning,
+}
+
+impl From<xcb::Error> for RuntimeError {
+    fn from(error: xcb::Error) -> Self {
+        RuntimeError::XError(error)
+    }
+}
+
+impl From<std::io::Error> for RuntimeError {
+    fn from(error: std::io::Error) -> Self {
+        RuntimeError::IoError(error)
+    }
+}
+
+impl From<xcb::ConnError> for RuntimeError {
+    fn from(error: xcb::ConnError) -> Self {
+        RuntimeError::XError(xcb::Error::Connection(error))
+    }
+}
 
-pub trait Compositor: Send + Sync {
-    fn locate_windows(&self);
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeError::XError(error) => write!(f, "{}", error),
+            RuntimeError::IoError(error) => write!(f, "{}", error),
+            RuntimeError::AnotherWindowManagerRunning => {
+                write!(f, "another window manager is already running")
+            }
+        }
+    }
 }
 
 pub fn print_runtime_error(error: RuntimeError) {
-    todo!()
+    eprintln!("Error {}", error)
+}
+
+#[derive(Clone, Copy)]
+enum StateChange {
+    Remove,
+    Add,
+    Toggle,
+}
+
+impl StateChange {
+    /// Action of a `_NET_WM_STATE` client message.
+    fn from_message(action: u32) -> Option<StateChange> {
+        match action {
+            0 => Some(StateChange::Remove),
+            1 => Some(StateChange::Add),
+            2 => Some(StateChange::Toggle),
+            _ => None,
+        }
+    }
+}
+
+/// Position that centers a window of the given size in the area.
+fn center(size: WindowSize, area: WindowGeometry) -> WindowPosition {
+    WindowPosition::new(
+        area.x() + (area.width() as i16 - size.width() as i16) / 2,
+        area.y() + (area.height() as i16 - size.height() as i16) / 2,
+    )
 }
 
 pub struct WindowManager {
-    config: Arc<Config>,
+    config: Config,
     x_server_connecton: Arc<Connection>,
-    compositor: Arc<RefCell<Box<dyn Compositor>>>,
+    atoms: Arc<Atoms>,
+    screen: Screen,
+    /// Layouts of the tags in tag order, by the monitor owning the tags, or
+    /// `None` for shared tags.
+    tag_layouts: HashMap<Option<String>, Vec<TagLayout>>,
+    /// Places the floating windows of every layout.
+    floating_compositor: FloatingCompositor,
+    title_bars: TitleBars,
+    windows: Vec<XWindow>,
+    /// Managed windows from bottom to top.
+    stacking: Vec<x::Window>,
+    /// Docks and desktops, which are mapped but not managed.
+    docks: Vec<(x::Window, Strut)>,
+    /// Tiled windows and the geometries the compositor gave them.
+    layout: Vec<(x::Window, WindowGeometry)>,
+    focused: Option<x::Window>,
+    focus_history: FocusHistory,
+    focus_cycle: Option<FocusCycle>,
+    drag: Option<Drag>,
+    event_handler: EventHandler,
+    /// Sequence number of the last layout change, see
+    /// `ignore_layout_crossings`.
+    layout_sequence: u16,
+    /// Server time of the last event with one, focus changes happen at that
+    /// time.
+    event_time: x::Timestamp,
+    /// Sequence number of the last SetInputFocus, see `on_focus_in`.
+    focus_sequence: u16,
+    ewmh: Ewmh,
+    ipc: IpcServer,
+    timers: Timers<Action>,
+    running: bool,
 }
 
-unsafe impl Sync for WindowManager {}
-unsafe impl Send for WindowManager {}
-
 impl WindowManager {
-    pub fn new(config: Config) -> WindowManager {
-        let connection = Connection::connect(None).unwrap().0;
+    pub fn new(
+        config: Config,
+        restored_state: Option<State>,
+    ) -> Result<WindowManager, RuntimeError> {
+        let (connection, screen_number) = Connection::connect_with_extensions(
+            None,
+            &[],
+            &[xcb::Extension::RandR, xcb::Extension::Xinerama],
+        )?;
         let connection = Arc::new(connection);
-        let config = Arc::new(config);
-        WindowManager {
-            config: config.clone(),
-            x_server_connecton: connection.clone(),
-            compositor: Arc::new(RefCell::new(Box::new(TilingCompositor::new(
-                config.tiling_config(),
-                connection,
-            )))),
+        let screen = Screen::new(
+            &connection,
+            screen_number,
+            config.tags().len() as u32,
+            config.monitor_tags(),
+        );
+        let atoms = Arc::new(Atoms::intern_all(&connection)?);
+        let event_time = Self::server_time(&connection, screen.root(), atoms.xrwm_time)?;
+        Self::become_window_manager(&connection, screen.root())?;
+        screen.select_changes(&connection);
+        let ewmh = Ewmh::new(connection.clone(), atoms.clone(), screen.root());
+        ewmh.set_desktops(&config.tags());
+        let floating_compositor = FloatingCompositor::new(config.window_config().border_size());
+        let title_bars = TitleBars::new(connection.clone(), screen.root());
+        let mut window_manager = WindowManager {
+            config,
+            x_server_connecton: connection,
+            atoms,
+            screen,
+            tag_layouts: HashMap::new(),
+            floating_compositor,
+            title_bars,
+            windows: Vec::new(),
+            stacking: Vec::new(),
+            docks: Vec::new(),
+            layout: Vec::new(),
+            focused: None,
+            focus_history: FocusHistory::default(),
+            focus_cycle: None,
+            drag: None,
+            event_handler: EventHandler::new(),
+            layout_sequence: 0,
+            event_time,
+            focus_sequence: 0,
+            ewmh,
+            ipc: IpcServer::bind()?,
+            timers: Timers::new(),
+            running: true,
+        };
+        window_manager.add_tag_layouts();
+        window_manager.adopt_windows(restored_state)?;
+        window_manager
+            .ewmh
+            .set_current_desktop(window_manager.current_tag());
+        Ok(window_manager)
+    }
+
+    /// Current server time, from the PropertyNotify of an empty append to a
+    /// root window property (ICCCM 2.1). No other events are selected yet,
+    /// so nothing else can arrive in the meantime.
+    fn server_time(
+        connection: &Connection,
+        root: x::Window,
+        property: x::Atom,
+    ) -> Result<x::Timestamp, RuntimeError> {
+        connection.send_request(&x::ChangeWindowAttributes {
+            window: root,
+            value_list: &[x::Cw::EventMask(x::EventMask::PROPERTY_CHANGE)],
+        });
+        connection.send_request(&x::ChangeProperty {
+            mode: x::PropMode::Append,
+            window: root,
+            property,
+            r#type: x::ATOM_CARDINAL,
+            data: &[] as &[u32],
+        });
+        connection.flush()?;
+        let time = loop {
+            if let Event::X(x::Event::PropertyNotify(event)) = connection.wait_for_event()? {
+                break event.time();
+            }
+        };
+        connection.send_request(&x::DeleteProperty {
+            window: root,
+            property,
+        });
+        Ok(time)
+    }
+
+    /// Selects `SubstructureRedirect` on the root window. Only one client may
+    /// hold it, so `BadAccess` means another window manager is running.
+    fn become_window_manager(connection: &Connection, root: x::Window) -> Result<(), RuntimeError> {
+        let cookie = connection.send_request_checked(&x::ChangeWindowAttributes {
+            window: root,
+            value_list: &[x::Cw::EventMask(
+                x::EventMask::SUBSTRUCTURE_REDIRECT | x::EventMask::SUBSTRUCTURE_NOTIFY,
+            )],
+        });
+        match connection.check_request(cookie) {
+            Ok(()) => Ok(()),
+            Err(xcb::ProtocolError::X(x::Error::Access(_), _)) => {
+                Err(RuntimeError::AnotherWindowManagerRunning)
+            }
+            Err(error) => Err(RuntimeError::XError(xcb::Error::Protocol(error))),
         }
     }
 
-    pub fn run(self) -> Result<(), RuntimeError> {
-        let window_manager = Arc::new(self);
+    /// Handles X events, IPC commands and timers in order on the current
+    /// thread until `quit` is received.
+    pub fn run(mut self) -> Result<(), RuntimeError> {
+        while self.running {
+            self.dispatch_x_events()?;
+            self.x_server_connecton.flush()?;
+            let timeout = match (self.timers.timeout(), self.ipc.timeout()) {
+                (Some(timers), Some(ipc)) => Some(timers.min(ipc)),
+                (timers, ipc) => timers.or(ipc),
+            };
+            let ipc_ready = event_loop::wait(
+                self.x_server_connecton.as_raw_fd(),
+                &self.ipc.fds(),
+                timeout,
+            )?;
+            if ipc_ready {
+                for request in self.ipc.accept() {
+                    self.handle_ipc_request(request);
+                }
+            }
+            self.ipc.drop_expired();
+            for action in self.timers.expired() {
+                if let Err(error) = self.run_action(action) {
+                    print_runtime_error(error);
+                }
+            }
+        }
+        Ok(())
+    }
 
+    /// Reads every event that is already available and handles them in
+    /// batches, so bursts of ConfigureNotify or MotionNotify are coalesced.
+    fn dispatch_x_events(&mut self) -> Result<(), RuntimeError> {
         loop {
-            let event = window_manager.x_server_connecton.wait_for_event()?;
-            let window_manager = window_manager.clone();
-            thread::spawn(move || window_manager.clone().handle_event(event));
+            let mut events = Vec::new();
+            loop {
+                match self.x_server_connecton.poll_for_event() {
+                    Ok(Some(event)) => events.push(event),
+                    Ok(None) => break,
+                    // Errors of unchecked requests, e.g. for a window that
+                    // was destroyed in the meantime, are not fatal.
+                    Err(xcb::Error::Protocol(error)) => {
+                        print_runtime_error(RuntimeError::XError(xcb::Error::Protocol(error)))
+                    }
+                    Err(error) => return Err(error.into()),
+                }
+            }
+            if events.is_empty() {
+                return Ok(());
+            }
+            for event in event_loop::coalesce(events) {
+                self.handle_event(event);
+            }
         }
     }
 
-    pub fn handle_event(&self, event: Event) {
-        match event {
-            Event::X(xvent) => todo!(),
-            Event::Shape(_) => todo!(),
-            Event::XFixes(_) => todo!(),
+    fn handle_ipc_request(&mut self, request: IpcRequest) {
+        let result = request
+            .command()
+            .parse()
+            .and_then(|action| self.run_action(action).map_err(|error| error.to_string()));
+        request.reply(result);
+    }
+
+    fn run_action(&mut self, action: Action) -> Result<(), RuntimeError> {
+        match action {
+            Action::Quit => self.running = false,
+            Action::Restart => return self.restart(),
+            Action::View(tag) => return self.view_tag(tag),
+            Action::ToggleState(state, window) => {
+                if let Some(window) = self.target_window(window).map(|window| window.window()) {
+                    return self.change_window_state(window, state, StateChange::Toggle);
+                }
+            }
+            Action::ToggleFloating(window) => {
+                if let Some(window) = self.target_window(window).map(|window| window.window()) {
+                    return self.toggle_floating(window);
+                }
+            }
+            Action::FocusLast => return self.focus_last(),
+            Action::CycleFocus(cycle) => return self.cycle_focus(cycle),
+            Action::Focus(direction) => {
+                if let Some(window) = self.neighbour_window(direction) {
+                    return self.activate_window(window);
+                }
+            }
+            Action::Swap(direction) => return self.swap_window(direction),
+            Action::SetLayout(layout) => return self.set_layout(layout),
+            Action::CycleLayout(cycle) => {
+                let all = LayoutKind::ALL;
+                let index = all
+                    .iter()
+                    .position(|layout| *layout == self.tag_layout().kind())
+                    .unwrap_or(0);
+                let next = match cycle {
+                    Cycle::Next => (index + 1) % all.len(),
+                    Cycle::Previous => (index + all.len() - 1) % all.len(),
+                };
+                return self.set_layout(all[next]);
+            }
+            Action::FocusStep(cycle) => return self.focus_step(cycle),
+            Action::FocusMonitor(target) => return self.focus_on_monitor(&target),
+            Action::SendToMonitor(target, window) => return self.send_to_monitor(&target, window),
+            Action::SwapMonitors(target) => return self.swap_monitors(&target),
+            Action::MoveTagToMonitor(target) => return self.move_tag_to_monitor(&target),
+            Action::Layout(message) => {
+                let focused = self.focused;
+                self.tag_layout_mut()
+                    .compositor_mut()
+                    .handle_message(message, focused);
+                return self.arrange();
+            }
+            Action::Close(window) => return self.close_window(window),
+            Action::Terminate(window) => return self.terminate_window(window),
+            Action::Kill(window) => {
+                if let Some(window) = self.target_window(window) {
+                    window.kill()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Changes the layout of the current tag. The parameters of the previous
+    /// layout are kept for when the tag switches back to it.
+    fn set_layout(&mut self, layout: LayoutKind) -> Result<(), RuntimeError> {
+        let tag_set = self.tag_set(self.screen.focused());
+        let index = (self.current_tag() - 1) as usize;
+        self.tag_layouts.get_mut(&tag_set).unwrap()[index].set_kind(layout, &self.config);
+        self.arrange()
+    }
+
+    fn tag_layout(&self) -> &TagLayout {
+        let tag_set = self.tag_set(self.screen.focused());
+        &self.tag_layouts[&tag_set][(self.current_tag() - 1) as usize]
+    }
+
+    fn tag_layout_mut(&mut self) -> &mut TagLayout {
+        let tag_set = self.tag_set(self.screen.focused());
+        let index = (self.current_tag() - 1) as usize;
+        &mut self.tag_layouts.get_mut(&tag_set).unwrap()[index]
+    }
+
+    /// Window an action applies to, the focused one if no window is given.
+    /// `None` if it isn't managed.
+    fn target_window(&self, window: Option<x::Window>) -> Option<&XWindow> {
+        self.find_window(window.or(self.focused)?)
+    }
+
+    /// Floating windows go back to the last geometry they had while they
+    /// floated, and are raised above the tiles.
+    fn toggle_floating(&mut self, window: x::Window) -> Result<(), RuntimeError> {
+        let Some(managed) = self.find_window(window) else {
+            return Ok(());
+        };
+        managed.set_floating(!managed.is_floating());
+        if managed.is_floating() {
+            self.raise(window);
+        }
+        self.arrange()
+    }
+
+    /// Closes the window gracefully. With `close_timeout` set, a window that
+    /// is still there after the timeout is terminated.
+    fn close_window(&mut self, window: Option<x::Window>) -> Result<(), RuntimeError> {
+        let Some(window) = self.target_window(window) else {
+            return Ok(());
+        };
+        window.close()?;
+        let id = window.window();
+        if let Some(timeout) = self.config.window_config().close_timeout() {
+            self.timers.schedule(timeout, Action::Terminate(Some(id)));
+        }
+        Ok(())
+    }
+
+    /// Sends SIGTERM to the client and kills its connection if it's still
+    /// there after `close_timeout`, or right away if the pid is unknown.
+    fn terminate_window(&mut self, window: Option<x::Window>) -> Result<(), RuntimeError> {
+        let Some(window) = self.target_window(window) else {
+            return Ok(());
+        };
+        if !window.terminate() {
+            return Ok(window.kill()?);
+        }
+        let id = window.window();
+        let timeout = self.config.window_config().close_timeout();
+        self.timers
+            .schedule(timeout.unwrap_or_default(), Action::Kill(Some(id)));
+        Ok(())
+    }
+
+    /// Saves the state of every managed window and replaces the process with
+    /// a new instance of the binary, which adopts the windows again.
+    fn restart(&mut self) -> Result<(), RuntimeError> {
+        let state = State {
+            current_tag: self.current_tag(),
+            monitor_tags: self
+                .screen
+                .monitors()
+                .iter()
+                .map(|monitor| monitor.tag())
+                .collect(),
+            focused: self.focused.map(|window| window.resource_id()),
+            windows: self
+                .windows
+                .iter()
+                .map(|window| WindowState {
+                    id: window.window().resource_id(),
+                    tag: window.current_tag(),
+                    monitor: window.monitor(),
+                    floating: window.floating_override(),
+                    geometry: window.saved_floating_geometry(),
+                })
+                .collect(),
+            layouts: self
+                .tag_layouts
+                .iter()
+                .flat_map(|(monitor, tag_layouts)| {
+                    tag_layouts.iter().map(|tag_layout| LayoutState {
+                        monitor: monitor.clone(),
+                        kind: tag_layout.kind(),
+                        state: tag_layout.compositor().save_state(),
+                    })
+                })
+                .collect(),
+        };
+        let path = restart::state_path()?;
+        state.save(&path)?;
+        self.x_server_connecton.flush()?;
+        Err(restart::exec(&path, self.x_server_connecton.as_raw_fd()).into())
+    }
+
+    pub fn handle_event(&mut self, event: Event) {
+        let result = match event {
+            Event::X(xvent) => self.handle_x_event(xvent),
+            Event::Shape(event) => self.handle_shape_event(event),
+            Event::XFixes(event) => self.handle_fix_event(event),
+            Event::RandR(event) => self.handle_randr_event(event),
             Event::Unknown(event) => self.handle_unknown_event(event),
+        };
+        if let Err(error) = result {
+            print_runtime_error(error);
         }
     }
 
-    pub fn handle_unknown_event(&self, event: UnknownEvent) {
-        todo!()
+    pub fn handle_unknown_event(&mut self, _event: UnknownEvent) -> Result<(), RuntimeError> {
+        Ok(())
     }
 
-    pub fn handle_shape_event(&self, event: xcb::shape::Event) {
+    pub fn handle_shape_event(&mut self, event: xcb::shape::Event) -> Result<(), RuntimeError> {
         match event {
-            xcb::shape::Event::Notify(_) => todo!(),
+            xcb::shape::Event::Notify(_) => Ok(()),
         }
     }
 
-    pub fn handle_fix_event(&self, event: xcb::xfixes::Event) {
+    pub fn handle_fix_event(&mut self, event: xcb::xfixes::Event) -> Result<(), RuntimeError> {
         match event {
-            xcb::xfixes::Event::SelectionNotify(_) => todo!(),
-            xcb::xfixes::Event::CursorNotify(_) => todo!(),
+            xcb::xfixes::Event::SelectionNotify(_) => Ok(()),
+            xcb::xfixes::Event::CursorNotify(_) => Ok(()),
         }
     }
 
-    pub fn handle_x_event(&self, event: xcb::x::Event) {
+    pub fn handle_x_event(&mut self, event: xcb::x::Event) -> Result<(), RuntimeError> {
+        if let Some(time) = focus::event_time(&event) {
+            self.event_time = time;
+        }
         match event {
-            xcb::x::Event::KeyPress(_) => todo!(),
-            xcb::x::Event::KeyRelease(_) => todo!(),
-            xcb::x::Event::ButtonPress(_) => todo!(),
-            xcb::x::Event::ButtonRelease(_) => todo!(),
-            xcb::x::Event::MotionNotify(_) => todo!(),
-            xcb::x::Event::EnterNotify(_) => todo!(),
-            xcb::x::Event::LeaveNotify(_) => todo!(),
-            xcb::x::Event::FocusIn(_) => todo!(),
-            xcb::x::Event::FocusOut(_) => todo!(),
-            xcb::x::Event::KeymapNotify(_) => todo!(),
-            xcb::x::Event::Expose(_) => todo!(),
-            xcb::x::Event::GraphicsExposure(_) => todo!(),
-            xcb::x::Event::NoExposure(_) => todo!(),
-            xcb::x::Event::VisibilityNotify(_) => todo!(),
-            xcb::x::Event::CreateNotify(_) => todo!(),
-            xcb::x::Event::DestroyNotify(_) => todo!(),
-            xcb::x::Event::UnmapNotify(_) => todo!(),
-            xcb::x::Event::MapNotify(_) => todo!(),
-            xcb::x::Event::MapRequest(_) => todo!(),
-            xcb::x::Event::ReparentNotify(_) => todo!(),
-            xcb::x::Event::ConfigureNotify(_) => todo!(),
-            xcb::x::Event::ConfigureRequest(_) => todo!(),
-            xcb::x::Event::GravityNotify(_) => todo!(),
-            xcb::x::Event::ResizeRequest(_) => todo!(),
-            xcb::x::Event::CirculateNotify(_) => todo!(),
-            xcb::x::Event::CirculateRequest(_) => todo!(),
-            xcb::x::Event::PropertyNotify(_) => todo!(),
-            xcb::x::Event::SelectionClear(_) => todo!(),
-            xcb::x::Event::SelectionRequest(_) => todo!(),
-            xcb::x::Event::SelectionNotify(_) => todo!(),
-            xcb::x::Event::ColormapNotify(_) => todo!(),
-            xcb::x::Event::ClientMessage(_) => todo!(),
-            xcb::x::Event::MappingNotify(_) => todo!(),
+            xcb::x::Event::MapRequest(event) => self.on_map_request(event),
+            xcb::x::Event::ConfigureRequest(event) => self.on_configure_request(event),
+            xcb::x::Event::UnmapNotify(event) => self.on_unmap_notify(event),
+            xcb::x::Event::DestroyNotify(event) => self.on_destroy_notify(event),
+            xcb::x::Event::PropertyNotify(event) => self.on_property_notify(event),
+            xcb::x::Event::ClientMessage(event) => self.on_client_message(event),
+            xcb::x::Event::FocusIn(event) => self.on_focus_in(event),
+            xcb::x::Event::KeyPress(event) => self.on_key_press(event),
+            xcb::x::Event::KeyRelease(event) => self.on_key_release(event),
+            xcb::x::Event::ButtonPress(event) => self.on_button_press(event),
+            xcb::x::Event::ButtonRelease(event) => self.on_button_release(event),
+            xcb::x::Event::MotionNotify(event) => self.on_motion_notify(event),
+            xcb::x::Event::EnterNotify(event) => self.on_enter_notify(event),
+            xcb::x::Event::LeaveNotify(event) => self.on_leave_notify(event),
+            xcb::x::Event::Expose(event) => {
+                if event.count() == 0 {
+                    self.draw_title_bars();
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Manages every top-level window that is already mapped, or that was
+    /// managed before a restart and is hidden on another tag. The state saved
+    /// by a restart restores the tags and the order of the windows.
+    fn adopt_windows(&mut self, restored_state: Option<State>) -> Result<(), RuntimeError> {
+        let cookie = self.x_server_connecton.send_request(&x::QueryTree {
+            window: self.screen.root(),
+        });
+        let children = self
+            .x_server_connecton
+            .wait_for_reply(cookie)?
+            .children()
+            .to_vec();
+        let cookies: Vec<_> = children
+            .iter()
+            .map(|window| {
+                (
+                    *window,
+                    self.x_server_connecton
+                        .send_request(&x::GetWindowAttributes { window: *window }),
+                    self.x_server_connecton.send_request(&x::GetProperty {
+                        delete: false,
+                        window: *window,
+                        property: self.atoms.xrwm_tag,
+                        r#type: x::ATOM_CARDINAL,
+                        long_offset: 0,
+                        long_length: 1,
+                    }),
+                )
+            })
+            .collect();
+        for (window, attributes_cookie, tag_cookie) in cookies {
+            let Ok(attributes) = self.x_server_connecton.wait_for_reply(attributes_cookie) else {
+                continue;
+            };
+            let tag = match self.x_server_connecton.wait_for_reply(tag_cookie) {
+                Ok(reply) => reply.value::<u32>().first().copied(),
+                Err(_) => None,
+            };
+            let tag = restored_state
+                .as_ref()
+                .and_then(|state| state.window(window.resource_id()))
+                .map(|window_state| window_state.tag)
+                .or(tag);
+            let viewable = attributes.map_state() == x::MapState::Viewable;
+            if attributes.override_redirect() || (!viewable && tag.is_none()) {
+                continue;
+            }
+            if viewable && self.adopt_dock(window) {
+                continue;
+            }
+            self.manage(window, tag.unwrap_or(self.current_tag()));
+            if let Some(monitor) = restored_state
+                .as_ref()
+                .and_then(|state| state.window(window.resource_id()))
+                .and_then(|window_state| window_state.monitor.clone())
+                .filter(|_| self.config.monitor_tags() == MonitorTags::PerMonitor)
+            {
+                self.windows.last().unwrap().set_monitor(Some(monitor));
+            }
+            if let Some(floating) = restored_state
+                .as_ref()
+                .and_then(|state| state.window(window.resource_id()))
+                .and_then(|window_state| window_state.floating)
+            {
+                self.windows.last().unwrap().set_floating(floating);
+            }
+            if let Some(geometry) = restored_state
+                .as_ref()
+                .and_then(|state| state.window(window.resource_id()))
+                .and_then(|window_state| window_state.geometry)
+            {
+                self.windows.last().unwrap().set_floating_geometry(geometry);
+            }
+            if viewable {
+                self.windows.last().unwrap().show()?;
+            }
+        }
+        let mut focused = None;
+        if let Some(state) = restored_state {
+            let monitors = self.screen.monitors_mut();
+            for (monitor, tag) in monitors.iter_mut().zip(&state.monitor_tags) {
+                monitor.set_tag(*tag);
+            }
+            if state.monitor_tags.is_empty() {
+                self.screen.focused_monitor_mut().set_tag(state.current_tag);
+            }
+            let mut positions: HashMap<Option<String>, usize> = HashMap::new();
+            for layout_state in &state.layouts {
+                let position = positions.entry(layout_state.monitor.clone()).or_default();
+                let tag_layouts = self
+                    .tag_layouts
+                    .entry(layout_state.monitor.clone())
+                    .or_insert_with(|| tag_layouts(&self.config));
+                if let Some(tag_layout) = tag_layouts.get_mut(*position) {
+                    tag_layout.set_kind(layout_state.kind, &self.config);
+                    if let Some(saved) = &layout_state.state {
+                        tag_layout.compositor_mut().restore_state(saved);
+                    }
+                }
+                *position += 1;
+            }
+            self.windows.sort_by_key(|window| {
+                state
+                    .position(window.window().resource_id())
+                    .unwrap_or(usize::MAX)
+            });
+            focused = state.focused.map(x::Window::new);
+        }
+        self.arrange()?;
+        if focused.is_some_and(|window| self.is_managed(window)) {
+            self.focus(focused)?;
+        }
+        Ok(())
+    }
+
+    fn on_map_request(&mut self, event: x::MapRequestEvent) -> Result<(), RuntimeError> {
+        let window = event.window();
+        if self.is_dock(window) || self.adopt_dock(window) {
+            self.x_server_connecton
+                .send_request(&x::MapWindow { window });
+            return self.arrange();
+        }
+        if !self.is_managed(window) {
+            // Transients go to the tag of their parent.
+            let parent = self
+                .parent_window(window)
+                .map(|parent| self.find_window(parent).unwrap());
+            let tag = parent.map_or(self.current_tag(), |parent| parent.current_tag());
+            let monitor = parent.map(|parent| parent.monitor());
+            self.manage(window, tag);
+            if let Some(monitor) = monitor {
+                self.find_window(window).unwrap().set_monitor(monitor);
+            }
+            if self.floats(self.find_window(window).unwrap()) {
+                self.place_floating_window(window)?;
+            }
+            self.arrange()?;
+            let managed = self.find_window(window).unwrap();
+            if self.monitor_of(managed).is_some()
+                && managed.window_type() != WindowType::Notification
+            {
+                self.focus(Some(window))?;
+            }
+            return Ok(());
+        }
+        self.arrange()
+    }
+
+    /// Keeps track of the window if it's a dock or a desktop, whose struts
+    /// reserve space at the screen edges. Returns `false` for other windows.
+    fn adopt_dock(&mut self, window: x::Window) -> bool {
+        if WindowType::read(&self.x_server_connecton, &self.atoms, window).is_managed() {
+            return false;
+        }
+        self.x_server_connecton
+            .send_request(&x::ChangeWindowAttributes {
+                window,
+                value_list: &[x::Cw::EventMask(x::EventMask::PROPERTY_CHANGE)],
+            });
+        let strut = Strut::read(&self.x_server_connecton, &self.atoms, window);
+        self.docks.push((window, strut));
+        true
+    }
+
+    fn is_dock(&self, window: x::Window) -> bool {
+        self.docks.iter().any(|(dock, _)| *dock == window)
+    }
+
+    /// Managed window the window is transient for.
+    fn parent_window(&self, window: x::Window) -> Option<x::Window> {
+        self.find_window(window)
+            .and_then(|window| window.transient_for())
+            .or_else(|| {
+                crate::window::read_property::<x::Window>(
+                    &self.x_server_connecton,
+                    window,
+                    x::ATOM_WM_TRANSIENT_FOR,
+                    x::ATOM_WINDOW,
+                    1,
+                )?
+                .first()
+                .copied()
+            })
+            .filter(|parent| self.is_managed(*parent))
+    }
+
+    /// Centers a new floating window over its parent, or over the usable area
+    /// if it has none. Notifications keep the position they asked for. The
+    /// window keeps this geometry while it floats.
+    fn place_floating_window(&mut self, window: x::Window) -> Result<(), RuntimeError> {
+        let Some(floating) = self.find_window(window) else {
+            return Ok(());
+        };
+        if !self
+            .config
+            .window_config()
+            .open_in_center_on_floating_mode()
+            || floating.window_type() == WindowType::Notification
+        {
+            return Ok(());
+        }
+        let parent = match self
+            .parent_window(window)
+            .and_then(|parent| self.find_window(parent))
+        {
+            Some(parent) => parent.geometry()?,
+            None => self.screen.focused_monitor().usable_area(),
+        };
+        let geometry = floating.floating_geometry()?;
+        let position = center(WindowSize::new(geometry.width(), geometry.height()), parent);
+        floating.set_floating_geometry(WindowGeometry::new(
+            position.x(),
+            position.y(),
+            geometry.width(),
+            geometry.height(),
+        ));
+        Ok(())
+    }
+
+    /// The screen without the space reserved by docks.
+    fn usable_area(&self) -> WindowGeometry {
+        let screen = self.screen.geometry();
+        self.docks
+            .iter()
+            .fold(Strut::default(), |reserved, (_, strut)| {
+                reserved.union(strut)
+            })
+            .shrink(screen, screen)
+    }
+
+    /// Unmanaged windows are configured as they ask. Floating windows may
+    /// move and resize themselves, the others are only told the geometry they
+    /// already have.
+    fn on_configure_request(
+        &mut self,
+        event: x::ConfigureRequestEvent,
+    ) -> Result<(), RuntimeError> {
+        if let Some(window) = self.find_window(event.window()) {
+            if !self.floats(window) || window.has_state(NetWmState::Fullscreen) {
+                window.send_configure_notify()?;
+                return Ok(());
+            }
+            let mask = event.value_mask();
+            let current = window.floating_geometry()?;
+            let geometry = WindowGeometry::new(
+                match mask.contains(x::ConfigWindowMask::X) {
+                    true => event.x(),
+                    false => current.x(),
+                },
+                match mask.contains(x::ConfigWindowMask::Y) {
+                    true => event.y(),
+                    false => current.y(),
+                },
+                match mask.contains(x::ConfigWindowMask::WIDTH) {
+                    true => event.width(),
+                    false => current.width(),
+                },
+                match mask.contains(x::ConfigWindowMask::HEIGHT) {
+                    true => event.height(),
+                    false => current.height(),
+                },
+            );
+            window.set_floating_geometry(geometry);
+            if self.monitor_of(window).is_some() {
+                window.change_geometry(geometry)?;
+            } else {
+                window.send_configure_notify()?;
+            }
+            return Ok(());
+        }
+        let mask = event.value_mask();
+        let mut value_list = Vec::new();
+        if mask.contains(x::ConfigWindowMask::X) {
+            value_list.push(x::ConfigWindow::X(event.x() as i32));
+        }
+        if mask.contains(x::ConfigWindowMask::Y) {
+            value_list.push(x::ConfigWindow::Y(event.y() as i32));
+        }
+        if mask.contains(x::ConfigWindowMask::WIDTH) {
+            value_list.push(x::ConfigWindow::Width(event.width() as u32));
+        }
+        if mask.contains(x::ConfigWindowMask::HEIGHT) {
+            value_list.push(x::ConfigWindow::Height(event.height() as u32));
+        }
+        if mask.contains(x::ConfigWindowMask::BORDER_WIDTH) {
+            value_list.push(x::ConfigWindow::BorderWidth(event.border_width() as u32));
+        }
+        if mask.contains(x::ConfigWindowMask::SIBLING) {
+            value_list.push(x::ConfigWindow::Sibling(event.sibling()));
+        }
+        if mask.contains(x::ConfigWindowMask::STACK_MODE) {
+            value_list.push(x::ConfigWindow::StackMode(event.stack_mode()));
+        }
+        self.x_server_connecton.send_request(&x::ConfigureWindow {
+            window: event.window(),
+            value_list: &value_list,
+        });
+        self.x_server_connecton.flush()?;
+        Ok(())
+    }
+
+    fn on_unmap_notify(&mut self, event: x::UnmapNotifyEvent) -> Result<(), RuntimeError> {
+        if self.is_dock(event.window()) {
+            self.docks.retain(|(dock, _)| *dock != event.window());
+            return self.arrange();
+        }
+        let expected = match self.find_window(event.window()) {
+            Some(window) => window.take_expected_unmap(),
+            None => return Ok(()),
+        };
+        if !expected {
+            self.unmanage(event.window());
+            self.x_server_connecton.send_request(&x::DeleteProperty {
+                window: event.window(),
+                property: self.atoms.xrwm_tag,
+            });
+            return self.arrange();
+        }
+        Ok(())
+    }
+
+    fn on_destroy_notify(&mut self, event: x::DestroyNotifyEvent) -> Result<(), RuntimeError> {
+        if self.is_dock(event.window()) {
+            self.docks.retain(|(dock, _)| *dock != event.window());
+            return self.arrange();
+        }
+        if !self.is_managed(event.window()) {
+            return Ok(());
+        }
+        self.unmanage(event.window());
+        self.arrange()
+    }
+
+    fn on_property_notify(&mut self, event: x::PropertyNotifyEvent) -> Result<(), RuntimeError> {
+        let struts = [self.atoms.net_wm_strut, self.atoms.net_wm_strut_partial];
+        if self.is_dock(event.window()) && struts.contains(&event.atom()) {
+            let strut = Strut::read(&self.x_server_connecton, &self.atoms, event.window());
+            for (dock, dock_strut) in &mut self.docks {
+                if *dock == event.window() {
+                    *dock_strut = strut;
+                }
+            }
+            return self.arrange();
+        }
+        if let Some(window) = self.find_window(event.window()) {
+            window.refresh_property(event.atom());
+        }
+        if [x::ATOM_WM_NAME, self.atoms.net_wm_name].contains(&event.atom()) {
+            self.draw_title_bars();
+        }
+        Ok(())
+    }
+
+    fn on_client_message(&mut self, event: x::ClientMessageEvent) -> Result<(), RuntimeError> {
+        let x::ClientMessageData::Data32(data) = event.data() else {
+            return Ok(());
+        };
+        let window = event.window();
+        match event.r#type() {
+            message if message == self.atoms.net_current_desktop => {
+                self.view_tag(ewmh::desktop_to_tag(data[0]))
+            }
+            // Requests of applications, rather than pagers, are subject to
+            // the focus stealing policy.
+            message if message == self.atoms.net_active_window => {
+                if data[0] == 1 && !self.allows_focus_stealing(window) {
+                    return Ok(());
+                }
+                // Time of the user action in the pager, if it sent one.
+                if data[1] != 0 {
+                    self.event_time = data[1];
+                }
+                self.activate_window(window)
+            }
+            message if message == self.atoms.net_wm_desktop && data[0] == ALL_DESKTOPS => {
+                self.change_window_state(window, NetWmState::Sticky, StateChange::Add)
+            }
+            message if message == self.atoms.net_wm_desktop => {
+                if let Some(window) = self.find_window(window) {
+                    window.set_state(NetWmState::Sticky, false);
+                }
+                self.move_window_to_tag(window, ewmh::desktop_to_tag(data[0]))
+            }
+            message if message == self.atoms.net_close_window => self.close_window(Some(window)),
+            message if message == self.atoms.net_wm_state => {
+                let Some(change) = StateChange::from_message(data[0]) else {
+                    return Ok(());
+                };
+                for atom in [data[1], data[2]] {
+                    let atom = x::Atom::new(atom);
+                    if let Some(state) = NetWmState::from_atom(atom, &self.atoms) {
+                        self.change_window_state(window, state, change)?;
+                    }
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn change_window_state(
+        &mut self,
+        window: x::Window,
+        state: NetWmState,
+        change: StateChange,
+    ) -> Result<(), RuntimeError> {
+        let Some(window) = self.find_window(window) else {
+            return Ok(());
+        };
+        let enabled = match change {
+            StateChange::Remove => false,
+            StateChange::Add => true,
+            StateChange::Toggle => !window.has_state(state),
+        };
+        window.set_state(state, enabled);
+        match (state, enabled) {
+            (NetWmState::Above, true) => window.set_state(NetWmState::Below, false),
+            (NetWmState::Below, true) => window.set_state(NetWmState::Above, false),
+            _ => {}
+        }
+        self.arrange()
+    }
+
+    /// Shows the tag on the focused monitor. If the monitors share the tags
+    /// and another monitor shows it, the monitors swap their tags.
+    fn view_tag(&mut self, tag: u32) -> Result<(), RuntimeError> {
+        if tag == 0 || tag as usize > self.config.tags().len() {
+            return Ok(());
+        }
+        let focused = self.screen.focused();
+        let current = self.current_tag();
+        if self.config.monitor_tags() == MonitorTags::Shared {
+            if let Some(other) = (0..self.screen.monitors().len())
+                .find(|other| *other != focused && self.screen.monitors()[*other].tag() == tag)
+            {
+                self.move_tag_windows(tag, other, focused)?;
+                self.move_tag_windows(current, focused, other)?;
+                self.screen.monitors_mut()[other].set_tag(current);
+            }
+        }
+        self.screen.focused_monitor_mut().set_tag(tag);
+        self.ewmh.set_current_desktop(tag);
+        self.arrange()
+    }
+
+    fn move_window_to_tag(&mut self, window: x::Window, tag: u32) -> Result<(), RuntimeError> {
+        if tag == 0 || tag as usize > self.config.tags().len() {
+            return Ok(());
+        }
+        let Some(window) = self.find_window(window) else {
+            return Ok(());
+        };
+        window.move_to(tag);
+        self.arrange()
+    }
+
+    /// Shows the tag of the window, on the monitor owning it if every
+    /// monitor has its own tags, puts the window on top of it and focuses it.
+    fn activate_window(&mut self, window: x::Window) -> Result<(), RuntimeError> {
+        let Some(managed) = self.find_window(window) else {
+            return Ok(());
+        };
+        if self.monitor_of(managed).is_none() {
+            let tag = managed.current_tag();
+            if let Some(index) = managed.monitor().and_then(|name| {
+                self.screen
+                    .monitors()
+                    .iter()
+                    .position(|monitor| monitor.name() == name)
+            }) {
+                self.focus_monitor(index);
+            }
+            self.view_tag(tag)?;
+        }
+        self.raise(window);
+        self.focus(Some(window))
+    }
+
+    /// Lets the compositor of every monitor place the visible tiled windows
+    /// of its tag in the usable area of the monitor, then maps them and
+    /// unmaps the rest. Fullscreen windows cover their monitor, floating ones
+    /// get their floating geometry. The focus moves on if the focused window
+    /// isn't visible anymore.
+    fn arrange(&mut self) -> Result<(), RuntimeError> {
+        self.update_usable_areas();
+        self.ewmh
+            .set_workarea(self.usable_area(), self.config.tags().len());
+        let monitors: Vec<(u32, WindowGeometry)> = self
+            .screen
+            .monitors()
+            .iter()
+            .map(|monitor| (monitor.tag(), monitor.usable_area()))
+            .collect();
+        let mut placements = Vec::new();
+        let mut layout = Vec::new();
+        let mut titles = Vec::new();
+        let mut hidden = Vec::new();
+        for (index, (tag, area)) in monitors.into_iter().enumerate() {
+            // A tag shown twice is only arranged on the first monitor.
+            if (0..index).any(|other| {
+                self.screen.monitors()[other].tag() == tag
+                    && self.tag_set(other) == self.tag_set(index)
+            }) {
+                continue;
+            }
+            let clients = self
+                .windows
+                .iter()
+                .filter(|window| self.monitor_of(window) == Some(index))
+                .filter(|window| !window.has_state(NetWmState::Fullscreen))
+                // A window that is being destroyed shouldn't keep the
+                // others from being arranged, its DestroyNotify follows.
+                .filter_map(|window| self.client(window).ok())
+                .collect::<Vec<Client>>();
+            let tag_set = self.tag_set(index);
+            let compositor =
+                self.tag_layouts.get_mut(&tag_set).unwrap()[(tag - 1) as usize].compositor_mut();
+            let monitor_placements = compositor.locate_windows(area, &clients);
+            titles.extend(compositor.title_bars());
+            hidden.extend(compositor.hidden_windows());
+            layout.extend(
+                monitor_placements
+                    .iter()
+                    .filter(|placement| {
+                        clients.iter().any(|client| {
+                            client.window() == placement.window() && !client.is_floating()
+                        })
+                    })
+                    .map(|placement| (placement.window(), placement.geometry())),
+            );
+            let unplaced: Vec<Client> = clients
+                .iter()
+                .filter(|client| {
+                    !monitor_placements
+                        .iter()
+                        .any(|placement| placement.window() == client.window())
+                })
+                .copied()
+                .collect();
+            placements.extend(monitor_placements);
+            placements.extend(self.floating_compositor.locate_windows(area, &unplaced));
+        }
+        self.layout = layout;
+        for window in &self.windows {
+            let Some(monitor) = self.monitor_of(window) else {
+                window.hide()?;
+                continue;
+            };
+            if window.has_state(NetWmState::Fullscreen) {
+                window.configure(self.screen.monitors()[monitor].geometry(), 0);
+            } else if let Some(placement) = placements
+                .iter()
+                .find(|placement| placement.window() == window.window())
+            {
+                window.configure(placement.geometry(), placement.border_size());
+            }
+            window.show()?;
+        }
+        self.title_bars.update(titles);
+        self.x_server_connecton.flush()?;
+        // Hidden tabs go to the bottom of their layer, under the shown ones.
+        self.stacking.sort_by_key(|window| !hidden.contains(window));
+        self.restack();
+        self.ignore_layout_crossings();
+        self.refocus()?;
+        self.update_layout_name();
+        self.draw_title_bars();
+        Ok(())
+    }
+
+    /// Titles of tabbed and stacked windows, the focused one highlighted.
+    fn draw_title_bars(&self) {
+        self.title_bars.draw(self.focused, |window| {
+            self.find_window(window)
+                .map_or_else(String::new, |window| window.name())
+        });
+    }
+
+    /// Names of the layouts of the current tag and of every tag, for bars.
+    fn update_layout_name(&self) {
+        let tiles = self.monitor_tiles(self.screen.focused());
+        let focused = tiles
+            .iter()
+            .position(|window| Some(*window) == self.focused);
+        let name = self.tag_layout().compositor().name(focused, tiles.len());
+        self.ewmh.set_layout_name(&name);
+        let tag_set = self.tag_set(self.screen.focused());
+        let names: Vec<String> = (1..)
+            .zip(&self.tag_layouts[&tag_set])
+            .map(|(tag, tag_layout)| match tag == self.current_tag() {
+                true => name.clone(),
+                false => {
+                    let count = self
+                        .windows
+                        .iter()
+                        .filter(|window| window.is_visible_on(tag) && window.monitor() == tag_set)
+                        .filter(|window| !window.has_state(NetWmState::Fullscreen))
+                        .filter(|window| !window.is_floating())
+                        .count();
+                    tag_layout.compositor().name(None, count)
+                }
+            })
+            .collect();
+        self.ewmh.set_layout_names(&names);
+    }
+
+    /// Focuses the next or previous tiled window of the focused monitor in
+    /// layout order.
+    fn focus_step(&mut self, cycle: Cycle) -> Result<(), RuntimeError> {
+        let tiles = self.monitor_tiles(self.screen.focused());
+        let count = tiles.len();
+        if count == 0 {
+            return Ok(());
+        }
+        let index = tiles
+            .iter()
+            .position(|window| Some(*window) == self.focused);
+        let next = match (index, cycle) {
+            (None, _) => 0,
+            (Some(index), Cycle::Next) => (index + 1) % count,
+            (Some(index), Cycle::Previous) => (index + count - 1) % count,
+        };
+        self.activate_window(tiles[next])
+    }
+
+    /// Tiled window next to the focused one, chosen by the geometries of the
+    /// last layout.
+    fn neighbour_window(&self, direction: Direction) -> Option<x::Window> {
+        let focused = self.focused?;
+        let (_, from) = self.layout.iter().find(|(window, _)| *window == focused)?;
+        let geometries: Vec<WindowGeometry> =
+            self.layout.iter().map(|(_, geometry)| *geometry).collect();
+        let index = direction::neighbour(*from, &geometries, direction)?;
+        Some(self.layout[index].0)
+    }
+
+    /// Swaps the places of the focused window and its neighbour, the focus
+    /// moves with the window.
+    fn swap_window(&mut self, direction: Direction) -> Result<(), RuntimeError> {
+        let (Some(focused), Some(neighbour)) = (self.focused, self.neighbour_window(direction))
+        else {
+            return Ok(());
+        };
+        let position = |window: x::Window| {
+            self.windows
+                .iter()
+                .position(|managed| managed.window() == window)
+        };
+        if let (Some(first), Some(second)) = (position(focused), position(neighbour)) {
+            self.windows.swap(first, second);
+        }
+        self.arrange()
+    }
+
+    fn find_window(&self, window: x::Window) -> Option<&XWindow> {
+        self.windows
+            .iter()
+            .find(|managed| managed.window() == window)
+    }
+
+    /// What the layouts know about the window.
+    fn client(&self, window: &XWindow) -> Result<Client, xcb::Error> {
+        let floating = self.floats(window);
+        Ok(Client::new(
+            window.window(),
+            window.size_hints(),
+            floating,
+            self.focused == Some(window.window()),
+            match floating {
+                true => Some(window.floating_geometry()?),
+                false => None,
+            },
+        ))
+    }
+
+    /// Floating windows are placed by the floating compositor and stacked
+    /// above the tiled ones.
+    fn floats(&self, window: &XWindow) -> bool {
+        if window.is_floating() {
+            return true;
+        }
+        // The layout the window is arranged with, a sticky window follows the
+        // monitor it's on.
+        let (tag_set, tag) = match self.monitor_of(window) {
+            Some(monitor) => (self.tag_set(monitor), self.screen.monitors()[monitor].tag()),
+            None => (window.monitor(), window.current_tag()),
+        };
+        self.tag_layouts
+            .get(&tag_set)
+            .and_then(|tag_layouts| tag_layouts.get(tag.checked_sub(1)? as usize))
+            .is_some_and(|tag_layout| tag_layout.compositor().is_floating())
+    }
+
+    fn is_managed(&self, window: x::Window) -> bool {
+        self.windows
+            .iter()
+            .any(|managed| managed.window() == window)
+    }
+
+    fn manage(&mut self, window: x::Window, tag: u32) {
+        self.x_server_connecton
+            .send_request(&x::ChangeWindowAttributes {
+                window,
+                value_list: &[x::Cw::EventMask(
+                    x::EventMask::PROPERTY_CHANGE
+                        | x::EventMask::FOCUS_CHANGE
+                        | x::EventMask::ENTER_WINDOW
+                        | x::EventMask::LEAVE_WINDOW,
+                )],
+            });
+        let window = XWindow::new(
+            window,
+            self.x_server_connecton.clone(),
+            self.atoms.clone(),
+            tag,
+            TagRules::All,
+        );
+        window.set_monitor(self.tag_set(self.screen.focused()));
+        self.stacking.push(window.window());
+        self.windows.push(window);
+        self.grab_buttons(self.windows.last().unwrap().window(), false);
+        self.update_client_list();
+    }
+
+    fn unmanage(&mut self, window: x::Window) {
+        self.windows.retain(|managed| managed.window() != window);
+        self.stacking.retain(|managed| *managed != window);
+        if self.focused == Some(window) {
+            self.focused = None;
         }
+        self.focus_history.remove(window);
+        self.update_client_list();
     }
 }
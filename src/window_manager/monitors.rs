@@ -0,0 +1,364 @@
+use xcb::{randr, x};
+
+use super::{
+    action::{Cycle, MonitorTarget},
+    center,
+    tag_layout::tag_layouts,
+    RuntimeError, WindowManager,
+};
+use crate::{
+    config::MonitorTags,
+    window::{
+        ewmh::{NetWmState, Strut},
+        Window, WindowGeometry, WindowSize, XWindow,
+    },
+};
+
+impl WindowManager {
+    /// Tag of the focused monitor, new windows go there.
+    pub(super) fn current_tag(&self) -> u32 {
+        self.screen.focused_monitor().tag()
+    }
+
+    /// Name of the monitor owning the tags the monitor shows, `None` when
+    /// the monitors share the tags.
+    pub(super) fn tag_set(&self, monitor: usize) -> Option<String> {
+        match self.config.monitor_tags() {
+            MonitorTags::Shared => None,
+            MonitorTags::PerMonitor => Some(self.screen.monitors()[monitor].name().to_string()),
+        }
+    }
+
+    /// Monitor showing the tag of the window, `None` if it's hidden. Sticky
+    /// windows stay on the monitor they are on.
+    pub(super) fn monitor_of(&self, window: &XWindow) -> Option<usize> {
+        if window.has_state(NetWmState::Hidden) {
+            return None;
+        }
+        let tag = window.current_tag();
+        let tag_set = window.monitor();
+        let monitors = self.screen.monitors();
+        if let Some(index) = (0..monitors.len())
+            .find(|index| monitors[*index].tag() == tag && self.tag_set(*index) == tag_set)
+        {
+            return Some(index);
+        }
+        if !window.has_state(NetWmState::Sticky) {
+            return None;
+        }
+        if let Some(name) = tag_set {
+            return monitors.iter().position(|monitor| monitor.name() == name);
+        }
+        let geometry = window.floating_geometry().ok()?;
+        self.screen
+            .monitor_at(
+                geometry.x().saturating_add_unsigned(geometry.width() / 2),
+                geometry.y().saturating_add_unsigned(geometry.height() / 2),
+            )
+            .or(Some(self.screen.focused()))
+    }
+
+    /// Tiled windows of the monitor, in layout order.
+    pub(super) fn monitor_tiles(&self, monitor: usize) -> Vec<x::Window> {
+        self.layout
+            .iter()
+            .map(|(window, _)| *window)
+            .filter(|window| {
+                self.find_window(*window)
+                    .and_then(|window| self.monitor_of(window))
+                    == Some(monitor)
+            })
+            .collect()
+    }
+
+    /// Creates the layouts of the tags of new monitors.
+    pub(super) fn add_tag_layouts(&mut self) {
+        for index in 0..self.screen.monitors().len() {
+            let tag_set = self.tag_set(index);
+            if !self.tag_layouts.contains_key(&tag_set) {
+                self.tag_layouts.insert(tag_set, tag_layouts(&self.config));
+            }
+        }
+    }
+
+    /// Takes the space reserved by docks from the monitors. Struts are
+    /// measured from the screen edges, so only monitors at an edge lose it.
+    pub(super) fn update_usable_areas(&mut self) {
+        let strut = self
+            .docks
+            .iter()
+            .fold(Strut::default(), |reserved, (_, strut)| {
+                reserved.union(strut)
+            });
+        let screen = self.screen.geometry();
+        for monitor in self.screen.monitors_mut() {
+            monitor.set_usable_area(strut.shrink(monitor.geometry(), screen));
+        }
+    }
+
+    /// New windows and tag changes go to the focused monitor.
+    pub(super) fn focus_monitor(&mut self, index: usize) {
+        self.screen.focus_monitor(index);
+        self.ewmh.set_current_desktop(self.current_tag());
+    }
+
+    fn monitor_index(&self, target: &MonitorTarget) -> Option<usize> {
+        let monitors = self.screen.monitors();
+        let focused = self.screen.focused();
+        match target {
+            MonitorTarget::Step(Cycle::Next) => Some((focused + 1) % monitors.len()),
+            MonitorTarget::Step(Cycle::Previous) => {
+                Some((focused + monitors.len() - 1) % monitors.len())
+            }
+            MonitorTarget::Name(name) => monitors.iter().position(|monitor| monitor.name() == name),
+        }
+    }
+
+    /// Focuses the monitor and the window that was focused last on it.
+    pub(super) fn focus_on_monitor(&mut self, target: &MonitorTarget) -> Result<(), RuntimeError> {
+        let Some(index) = self.monitor_index(target) else {
+            return Ok(());
+        };
+        self.focus_monitor(index);
+        self.focus(self.last_focused_on(index))
+    }
+
+    fn last_focused_on(&self, monitor: usize) -> Option<x::Window> {
+        self.focus_order().into_iter().find(|window| {
+            self.find_window(*window)
+                .and_then(|window| self.monitor_of(window))
+                == Some(monitor)
+        })
+    }
+
+    /// Floating windows keep their place relative to the monitor.
+    fn move_floating(&self, window: &XWindow, from: usize, to: usize) -> Result<(), RuntimeError> {
+        let (from, to) = (
+            self.screen.monitors()[from].geometry(),
+            self.screen.monitors()[to].geometry(),
+        );
+        let geometry = window.floating_geometry()?;
+        window.set_floating_geometry(WindowGeometry::new(
+            geometry.x() - from.x() + to.x(),
+            geometry.y() - from.y() + to.y(),
+            geometry.width(),
+            geometry.height(),
+        ));
+        Ok(())
+    }
+
+    /// Moves the floating windows of a tag along when another monitor shows
+    /// it.
+    pub(super) fn move_tag_windows(
+        &self,
+        tag: u32,
+        from: usize,
+        to: usize,
+    ) -> Result<(), RuntimeError> {
+        let tag_set = self.tag_set(from);
+        for window in &self.windows {
+            if window.current_tag() == tag
+                && window.monitor() == tag_set
+                && !window.has_state(NetWmState::Sticky)
+            {
+                self.move_floating(window, from, to)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The focus stays on the focused monitor.
+    pub(super) fn send_to_monitor(
+        &mut self,
+        target: &MonitorTarget,
+        window: Option<x::Window>,
+    ) -> Result<(), RuntimeError> {
+        let (Some(index), Some(managed)) = (self.monitor_index(target), self.target_window(window))
+        else {
+            return Ok(());
+        };
+        let from = self.monitor_of(managed).unwrap_or(self.screen.focused());
+        if from == index {
+            return Ok(());
+        }
+        let window = managed.window();
+        self.move_floating(managed, from, index)?;
+        managed.set_monitor(self.tag_set(index));
+        managed.move_to(self.screen.monitors()[index].tag());
+        self.arrange()?;
+        if self.focused == Some(window) {
+            self.focus(self.last_focused_on(self.screen.focused()))?;
+        }
+        Ok(())
+    }
+
+    /// With shared tags the monitors swap their tags, otherwise the windows
+    /// and layouts of the two shown tags change places.
+    pub(super) fn swap_monitors(&mut self, target: &MonitorTarget) -> Result<(), RuntimeError> {
+        let focused = self.screen.focused();
+        let Some(other) = self.monitor_index(target).filter(|other| *other != focused) else {
+            return Ok(());
+        };
+        let (first, second) = (
+            self.screen.monitors()[focused].tag(),
+            self.screen.monitors()[other].tag(),
+        );
+        self.move_tag_windows(first, focused, other)?;
+        self.move_tag_windows(second, other, focused)?;
+        match self.config.monitor_tags() {
+            MonitorTags::Shared => {
+                self.screen.monitors_mut()[focused].set_tag(second);
+                self.screen.monitors_mut()[other].set_tag(first);
+            }
+            MonitorTags::PerMonitor => {
+                let (first_set, second_set) = (self.tag_set(focused), self.tag_set(other));
+                for window in &self.windows {
+                    if window.has_state(NetWmState::Sticky) {
+                        continue;
+                    }
+                    if window.monitor() == first_set && window.current_tag() == first {
+                        window.set_monitor(second_set.clone());
+                        window.move_to(second);
+                    } else if window.monitor() == second_set && window.current_tag() == second {
+                        window.set_monitor(first_set.clone());
+                        window.move_to(first);
+                    }
+                }
+                self.swap_tag_layouts((&first_set, first), (&second_set, second));
+            }
+        }
+        self.ewmh.set_current_desktop(self.current_tag());
+        self.arrange()
+    }
+
+    /// With shared tags the other monitor shows the tag, and the focused one
+    /// the first tag no monitor shows. Otherwise the windows join the same
+    /// tag of the other monitor and the layout of the tag goes with them, the
+    /// layout it replaces is left to the focused monitor. The focus follows
+    /// the tag.
+    pub(super) fn move_tag_to_monitor(
+        &mut self,
+        target: &MonitorTarget,
+    ) -> Result<(), RuntimeError> {
+        let focused = self.screen.focused();
+        let Some(other) = self.monitor_index(target).filter(|other| *other != focused) else {
+            return Ok(());
+        };
+        let tag = self.current_tag();
+        self.move_tag_windows(tag, focused, other)?;
+        match self.config.monitor_tags() {
+            MonitorTags::Shared => {
+                let replaced = self.screen.monitors()[other].tag();
+                let tags = self.config.tags().len() as u32;
+                let free = (1..=tags)
+                    .find(|free| {
+                        !self
+                            .screen
+                            .monitors()
+                            .iter()
+                            .any(|monitor| monitor.tag() == *free)
+                    })
+                    .unwrap_or(replaced);
+                if free == replaced {
+                    self.move_tag_windows(replaced, other, focused)?;
+                }
+                self.screen.monitors_mut()[focused].set_tag(free);
+            }
+            MonitorTags::PerMonitor => {
+                let (from_set, to_set) = (self.tag_set(focused), self.tag_set(other));
+                for window in &self.windows {
+                    if window.monitor() == from_set
+                        && window.current_tag() == tag
+                        && !window.has_state(NetWmState::Sticky)
+                    {
+                        window.set_monitor(to_set.clone());
+                    }
+                }
+                self.swap_tag_layouts((&from_set, tag), (&to_set, tag));
+            }
+        }
+        self.screen.monitors_mut()[other].set_tag(tag);
+        self.arrange()?;
+        self.focus_on_monitor(&MonitorTarget::Name(
+            self.screen.monitors()[other].name().to_string(),
+        ))
+    }
+
+    /// Exchanges the layouts of two tags of different tag sets.
+    fn swap_tag_layouts(&mut self, first: (&Option<String>, u32), second: (&Option<String>, u32)) {
+        if first.0 == second.0 {
+            return;
+        }
+        let Some(mut first_layouts) = self.tag_layouts.remove(first.0) else {
+            return;
+        };
+        if let Some(second_layouts) = self.tag_layouts.get_mut(second.0) {
+            let first_layout = first_layouts.get_mut(first.1 as usize - 1);
+            let second_layout = second_layouts.get_mut(second.1 as usize - 1);
+            if let (Some(first_layout), Some(second_layout)) = (first_layout, second_layout) {
+                std::mem::swap(first_layout, second_layout);
+            }
+        }
+        self.tag_layouts.insert(first.0.clone(), first_layouts);
+    }
+
+    pub(super) fn handle_randr_event(&mut self, event: randr::Event) -> Result<(), RuntimeError> {
+        match event {
+            randr::Event::ScreenChangeNotify(_) => self.on_screen_change(),
+            randr::Event::Notify(_) => Ok(()),
+        }
+    }
+
+    /// Outputs were added or removed. Windows stay on their tag, with tags per
+    /// monitor the tags of a monitor that is gone move to the focused
+    /// monitor. Floating windows that are off every monitor are centered on
+    /// it.
+    fn on_screen_change(&mut self) -> Result<(), RuntimeError> {
+        let tags = self.config.tags().len() as u32;
+        self.screen
+            .update(&self.x_server_connecton, tags, self.config.monitor_tags());
+        self.update_usable_areas();
+        self.add_tag_layouts();
+        let home = self.current_tag();
+        let home_set = self.tag_set(self.screen.focused());
+        let area = self.screen.focused_monitor().usable_area();
+        for window in &self.windows {
+            if let Some(name) = window.monitor() {
+                if !self
+                    .screen
+                    .monitors()
+                    .iter()
+                    .any(|monitor| monitor.name() == name)
+                {
+                    window.set_monitor(home_set.clone());
+                }
+            }
+            // Tiled windows are placed by their layout, only floating
+            // windows that were placed before may be left off screen.
+            let Some(geometry) = window
+                .saved_floating_geometry()
+                .filter(|_| self.floats(window))
+            else {
+                continue;
+            };
+            let on_monitor = self
+                .screen
+                .monitor_at(
+                    geometry.x().saturating_add_unsigned(geometry.width() / 2),
+                    geometry.y().saturating_add_unsigned(geometry.height() / 2),
+                )
+                .is_some();
+            if !on_monitor {
+                let position = center(WindowSize::new(geometry.width(), geometry.height()), area);
+                window.set_floating_geometry(WindowGeometry::new(
+                    position.x(),
+                    position.y(),
+                    geometry.width(),
+                    geometry.height(),
+                ));
+            }
+        }
+        self.ewmh.set_current_desktop(home);
+        self.arrange()
+    }
+}
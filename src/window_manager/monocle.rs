@@ -0,0 +1,115 @@
+use xcb::x;
+
+use super::{
+    compositor::{Client, Compositor, LayoutMessage, Placement},
+    tiling::{change_gap, fit, shrink, tiled},
+};
+use crate::{config::TilingConfig, window::WindowGeometry};
+
+/// Every window fills the area, the focused one is on top.
+pub struct MonocleCompositor {
+    border_size: u16,
+    outer_gap: u16,
+    smart_borders: bool,
+    smart_gaps: bool,
+}
+
+impl MonocleCompositor {
+    pub fn new(tiling_config: TilingConfig, border_size: u16) -> MonocleCompositor {
+        MonocleCompositor {
+            border_size,
+            outer_gap: tiling_config
+                .gaps_config()
+                .map_or(0, |gaps| gaps.outer_size()),
+            smart_borders: tiling_config.smart_borders(),
+            smart_gaps: tiling_config.smart_gaps(),
+        }
+    }
+}
+
+impl Compositor for MonocleCompositor {
+    fn locate_windows(&mut self, area: WindowGeometry, clients: &[Client]) -> Vec<Placement> {
+        let clients = tiled(clients);
+        // Several windows are still in the layout when only the focused one
+        // is seen.
+        let single = clients.len() == 1;
+        let border_size = match single && self.smart_borders {
+            true => 0,
+            false => self.border_size,
+        };
+        let area = match single && self.smart_gaps {
+            true => area,
+            false => shrink(area, self.outer_gap),
+        };
+        clients
+            .iter()
+            .map(|client| {
+                let geometry = fit(area, border_size, &client.size_hints());
+                Placement::new(client.window(), geometry, border_size)
+            })
+            .collect()
+    }
+
+    /// `[n/m]`, the focused window is the n-th of m. Just `[m]` when none
+    /// of them is focused.
+    fn name(&self, focused: Option<usize>, count: usize) -> String {
+        match focused {
+            Some(index) => format!("[{}/{}]", index + 1, count),
+            None => format!("[{}]", count),
+        }
+    }
+
+    fn handle_message(&mut self, message: LayoutMessage, _focused: Option<x::Window>) {
+        if let LayoutMessage::ChangeGaps(delta) = message {
+            self.outer_gap = change_gap(self.outer_gap, delta);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_manager::compositor::tests::{clients, window};
+
+    fn monocle(smart: bool) -> MonocleCompositor {
+        MonocleCompositor {
+            border_size: 2,
+            outer_gap: 10,
+            smart_borders: smart,
+            smart_gaps: smart,
+        }
+    }
+
+    #[test]
+    fn every_window_fills_the_area() {
+        let area = WindowGeometry::new(0, 0, 800, 600);
+        let placements = monocle(false).locate_windows(area, &clients(2));
+        assert_eq!(
+            placements,
+            vec![
+                Placement::new(window(1), WindowGeometry::new(10, 10, 776, 576), 2),
+                Placement::new(window(2), WindowGeometry::new(10, 10, 776, 576), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn smart_borders_and_gaps_need_a_single_window() {
+        let area = WindowGeometry::new(0, 0, 800, 600);
+        let mut monocle = monocle(true);
+        assert_eq!(
+            monocle.locate_windows(area, &clients(1)),
+            vec![Placement::new(window(1), area, 0)]
+        );
+        assert_eq!(
+            monocle.locate_windows(area, &clients(2))[0],
+            Placement::new(window(1), WindowGeometry::new(10, 10, 776, 576), 2)
+        );
+    }
+
+    #[test]
+    fn name_counts_the_windows() {
+        assert_eq!(monocle(false).name(Some(1), 3), "[2/3]");
+        assert_eq!(monocle(false).name(None, 3), "[3]");
+    }
+}
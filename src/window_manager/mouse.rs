@@ -0,0 +1,173 @@
+use xcb::x;
+
+use super::{
+    event_handler::{modifiers, LOCK_MASKS},
+    RuntimeError, WindowManager,
+};
+use crate::{
+    config::MouseModifier,
+    window::{ewmh::NetWmState, Window, WindowGeometry},
+};
+
+const MOVE_BUTTON: x::Button = 1;
+const RESIZE_BUTTON: x::Button = 3;
+
+/// A window moved or resized with the mouse.
+pub struct Drag {
+    window: x::Window,
+    resize: bool,
+    pointer: (i16, i16),
+    geometry: WindowGeometry,
+}
+
+fn modifier_mask(modifier: MouseModifier) -> x::ModMask {
+    match modifier {
+        MouseModifier::Super => x::ModMask::N4,
+        MouseModifier::Alt => x::ModMask::N1,
+        MouseModifier::Control => x::ModMask::CONTROL,
+        MouseModifier::Shift => x::ModMask::SHIFT,
+    }
+}
+
+impl WindowManager {
+    /// Grabs the move and resize buttons. On unfocused windows every click is
+    /// also grabbed, the pointer is frozen until the click focused the window
+    /// and was replayed to the client.
+    pub(super) fn grab_buttons(&self, window: x::Window, focused: bool) {
+        if !self.is_managed(window) {
+            return;
+        }
+        self.x_server_connecton.send_request(&x::UngrabButton {
+            button: x::ButtonIndex::Any,
+            grab_window: window,
+            modifiers: x::ModMask::ANY,
+        });
+        if !focused {
+            self.x_server_connecton.send_request(&x::GrabButton {
+                owner_events: false,
+                grab_window: window,
+                event_mask: x::EventMask::BUTTON_PRESS,
+                pointer_mode: x::GrabMode::Sync,
+                keyboard_mode: x::GrabMode::Async,
+                confine_to: x::WINDOW_NONE,
+                cursor: x::CURSOR_NONE,
+                button: x::ButtonIndex::Any,
+                modifiers: x::ModMask::ANY,
+            });
+        }
+        let modifier = modifier_mask(self.config.window_config().mouse_modifier());
+        for button in [x::ButtonIndex::N1, x::ButtonIndex::N3] {
+            for locks in LOCK_MASKS {
+                self.x_server_connecton.send_request(&x::GrabButton {
+                    owner_events: false,
+                    grab_window: window,
+                    event_mask: x::EventMask::BUTTON_PRESS
+                        | x::EventMask::BUTTON_RELEASE
+                        | x::EventMask::POINTER_MOTION,
+                    pointer_mode: x::GrabMode::Async,
+                    keyboard_mode: x::GrabMode::Async,
+                    confine_to: x::WINDOW_NONE,
+                    cursor: x::CURSOR_NONE,
+                    button,
+                    modifiers: modifier | locks,
+                });
+            }
+        }
+    }
+
+    pub(super) fn on_button_press(
+        &mut self,
+        event: x::ButtonPressEvent,
+    ) -> Result<(), RuntimeError> {
+        let window = event.event();
+        if let Some(client) = self.title_bars.client(window) {
+            self.focus(Some(client))?;
+            return self.arrange();
+        }
+        if self.is_managed(window) {
+            self.raise(window);
+            self.focus(Some(window))?;
+        }
+        let modifier = modifier_mask(self.config.window_config().mouse_modifier());
+        let button = event.detail();
+        if modifiers(event.state()) == modifier && [MOVE_BUTTON, RESIZE_BUTTON].contains(&button) {
+            return self.start_drag(window, button == RESIZE_BUTTON, event);
+        }
+        self.x_server_connecton.send_request(&x::AllowEvents {
+            mode: x::Allow::ReplayPointer,
+            time: event.time(),
+        });
+        Ok(())
+    }
+
+    /// Only windows that float can be moved and resized.
+    fn start_drag(
+        &mut self,
+        window: x::Window,
+        resize: bool,
+        event: x::ButtonPressEvent,
+    ) -> Result<(), RuntimeError> {
+        let Some(managed) = self.find_window(window) else {
+            return Ok(());
+        };
+        if !self.floats(managed) || managed.has_state(NetWmState::Fullscreen) {
+            return Ok(());
+        }
+        self.drag = Some(Drag {
+            window,
+            resize,
+            pointer: (event.root_x(), event.root_y()),
+            geometry: managed.floating_geometry()?,
+        });
+        Ok(())
+    }
+
+    /// Moves or resizes the dragged window, sizes follow the size hints of
+    /// the window, including the resize increments.
+    pub(super) fn on_motion_notify(
+        &mut self,
+        event: x::MotionNotifyEvent,
+    ) -> Result<(), RuntimeError> {
+        let Some(drag) = &self.drag else {
+            return Ok(());
+        };
+        let Some(window) = self.find_window(drag.window) else {
+            self.drag = None;
+            return Ok(());
+        };
+        let dx = event.root_x() as i32 - drag.pointer.0 as i32;
+        let dy = event.root_y() as i32 - drag.pointer.1 as i32;
+        let start = drag.geometry;
+        let geometry = match drag.resize {
+            true => {
+                let (width, height) = window.size_hints().constrain(
+                    (start.width() as i32 + dx).max(1) as u32,
+                    (start.height() as i32 + dy).max(1) as u32,
+                );
+                WindowGeometry::new(
+                    start.x(),
+                    start.y(),
+                    width.min(u16::MAX as u32) as u16,
+                    height.min(u16::MAX as u32) as u16,
+                )
+            }
+            false => WindowGeometry::new(
+                (start.x() as i32 + dx) as i16,
+                (start.y() as i32 + dy) as i16,
+                start.width(),
+                start.height(),
+            ),
+        };
+        window.set_floating_geometry(geometry);
+        window.change_geometry(geometry)?;
+        Ok(())
+    }
+
+    pub(super) fn on_button_release(
+        &mut self,
+        _event: x::ButtonReleaseEvent,
+    ) -> Result<(), RuntimeError> {
+        self.drag = None;
+        Ok(())
+    }
+}
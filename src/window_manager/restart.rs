@@ -0,0 +1,229 @@
+//! State handed over to the new process on an in-place restart.
+//!
+//! Before `exec`, the state is written as TOML to `state_path()` and the new
+//! process is started with `--restore <path>`. It is read back while the
+//! already existing windows are adopted, then the file is removed.
+//!
+//! ```toml
+//! current_tag = 2
+//! monitor_tags = [2, 1]
+//! focused = 4194311
+//!
+//! # in layout order
+//! [[windows]]
+//! id = 4194311
+//! tag = 2
+//! floating = true
+//!
+//! [windows.geometry]
+//! x = 100
+//! y = 80
+//! width = 640
+//! height = 480
+//!
+//! [[windows]]
+//! id = 6291463
+//! tag = 1
+//!
+//! # in tag order, per monitor if every monitor has its own tags
+//! [[layouts]]
+//! kind = "bsp"
+//! state = """
+//! [tree.split]
+//! orientation = "horizontal"
+//! ratio = 0.5
+//!
+//! [tree.split.first]
+//! leaf = 4194311
+//!
+//! [tree.split.second]
+//! leaf = 6291463
+//! """
+//! ```
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, ErrorKind, Write},
+    os::{
+        fd::RawFd,
+        unix::{fs::OpenOptionsExt, process::CommandExt},
+    },
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::ipc::runtime_dir;
+use crate::{config::LayoutKind, window::WindowGeometry};
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+pub struct State {
+    pub current_tag: u32,
+    /// Tag of every monitor, in the order the server lists them.
+    #[serde(default)]
+    pub monitor_tags: Vec<u32>,
+    pub focused: Option<u32>,
+    #[serde(default)]
+    pub windows: Vec<WindowState>,
+    #[serde(default)]
+    pub layouts: Vec<LayoutState>,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+pub struct WindowState {
+    pub id: u32,
+    pub tag: u32,
+    /// Monitor owning the tag, if every monitor has its own tags.
+    #[serde(default)]
+    pub monitor: Option<String>,
+    /// Only saved for windows that were toggled.
+    #[serde(default)]
+    pub floating: Option<bool>,
+    /// Position and size of windows that have been floating.
+    #[serde(default)]
+    pub geometry: Option<WindowGeometry>,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+pub struct LayoutState {
+    /// Monitor owning the tag, if every monitor has its own tags.
+    #[serde(default)]
+    pub monitor: Option<String>,
+    pub kind: LayoutKind,
+    /// Saved by the layout itself, see `Compositor::save_state`.
+    pub state: Option<String>,
+}
+
+pub fn state_path() -> io::Result<PathBuf> {
+    let display = std::env::var("DISPLAY")
+        .unwrap_or_default()
+        .replace('/', "_");
+    Ok(runtime_dir()?.join(format!("xrwm{}-state.toml", display)))
+}
+
+impl State {
+    /// Writes a new file only the user can read. A file left over by a
+    /// failed restart is replaced, links aren't followed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let content =
+            toml::to_string(self).map_err(|error| io::Error::new(ErrorKind::InvalidData, error))?;
+        match fs::remove_file(path) {
+            Err(error) if error.kind() != ErrorKind::NotFound => return Err(error),
+            _ => {}
+        }
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(path)?
+            .write_all(content.as_bytes())
+    }
+
+    pub fn load(path: &Path) -> io::Result<State> {
+        let content = fs::read_to_string(path)?;
+        toml::from_str(&content).map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+    }
+
+    pub fn window(&self, id: u32) -> Option<&WindowState> {
+        self.windows.iter().find(|window| window.id == id)
+    }
+
+    /// Position of the window in the saved layout order.
+    pub fn position(&self, id: u32) -> Option<usize> {
+        self.windows.iter().position(|window| window.id == id)
+    }
+}
+
+/// Replaces the current process with a fresh instance of the binary. Only
+/// returns on failure.
+pub fn exec(state_path: &Path, x_connection: RawFd) -> io::Error {
+    // The new process has to open its own connection, otherwise this one
+    // keeps SubstructureRedirect on the root window.
+    unsafe { libc::fcntl(x_connection, libc::F_SETFD, libc::FD_CLOEXEC) };
+    let binary = match std::env::current_exe() {
+        Ok(binary) => binary,
+        Err(error) => return error,
+    };
+    // After a rebuild the link names the replaced file.
+    let binary = binary
+        .to_str()
+        .and_then(|path| path.strip_suffix(" (deleted)"))
+        .map_or(binary.clone(), PathBuf::from);
+    Command::new(binary).arg("--restore").arg(state_path).exec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> State {
+        State {
+            current_tag: 2,
+            monitor_tags: vec![2, 1],
+            focused: Some(4194311),
+            windows: vec![
+                WindowState {
+                    id: 4194311,
+                    tag: 2,
+                    monitor: Some("HDMI-1".to_string()),
+                    floating: Some(true),
+                    geometry: Some(WindowGeometry::new(100, -20, 640, 480)),
+                },
+                WindowState {
+                    id: 6291463,
+                    tag: 1,
+                    monitor: None,
+                    floating: None,
+                    geometry: None,
+                },
+            ],
+            layouts: vec![
+                LayoutState {
+                    monitor: Some("HDMI-1".to_string()),
+                    kind: LayoutKind::Bsp,
+                    state: Some("[tree.split]\norientation = \"horizontal\"\n".to_string()),
+                },
+                LayoutState {
+                    monitor: None,
+                    kind: LayoutKind::Tile,
+                    state: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn state_round_trips_through_the_file() {
+        let path = std::env::temp_dir().join(format!("xrwm-state-{}.toml", std::process::id()));
+        let state = state();
+        state.save(&path).unwrap();
+        // A second save replaces the file of the first one.
+        state.save(&path).unwrap();
+        let loaded = State::load(&path);
+        let _ = fs::remove_file(&path);
+        assert_eq!(loaded.unwrap(), state);
+    }
+
+    #[test]
+    fn missing_fields_take_their_defaults() {
+        let state: State = toml::from_str(
+            "current_tag = 3\n\n[[windows]]\nid = 1\ntag = 3\n\n[[layouts]]\nkind = \"monocle\"\n",
+        )
+        .unwrap();
+        assert!(state.monitor_tags.is_empty());
+        assert_eq!(state.focused, None);
+        assert_eq!(state.windows[0].monitor, None);
+        assert_eq!(state.windows[0].geometry, None);
+        assert_eq!(state.layouts[0].kind, LayoutKind::Monocle);
+        assert_eq!(state.layouts[0].state, None);
+    }
+
+    #[test]
+    fn windows_are_found_by_id() {
+        let state = state();
+        assert_eq!(state.window(6291463).map(|window| window.tag), Some(1));
+        assert_eq!(state.position(6291463), Some(1));
+        assert_eq!(state.position(1), None);
+    }
+}
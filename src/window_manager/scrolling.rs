@@ -0,0 +1,427 @@
+use serde::{Deserialize, Serialize};
+use xcb::{x, Xid};
+
+use super::{
+    compositor::{Client, Compositor, LayoutMessage, Placement},
+    direction::Direction,
+    tiling::{change_gap, place, shrink, split, tiled},
+};
+use crate::{config::TilingConfig, window::WindowGeometry};
+
+const MIN_COLUMN_WIDTH: f32 = 0.1;
+const MAX_COLUMN_WIDTH: f32 = 1.0;
+
+/// Windows stacked in a column of the strip.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+struct Column {
+    windows: Vec<u32>,
+    /// Part of the area width.
+    width: f32,
+}
+
+/// Columns follow each other on a strip that is as wide as they need, the
+/// area is a viewport that scrolls to show the focused column. Columns that
+/// don't fit whole in the viewport are moved left of the root window, where
+/// no monitor shows them. They stay mapped.
+pub struct ScrollingCompositor {
+    columns: Vec<Column>,
+    /// Position of the viewport on the strip.
+    offset: i32,
+    column_width: f32,
+    border_size: u16,
+    inner_gap: u16,
+    outer_gap: u16,
+    smart_borders: bool,
+    smart_gaps: bool,
+}
+
+/// Saved on restart.
+#[derive(Serialize, Deserialize)]
+struct ScrollingState {
+    columns: Vec<Column>,
+    offset: i32,
+}
+
+impl ScrollingCompositor {
+    pub fn new(tiling_config: TilingConfig, border_size: u16) -> ScrollingCompositor {
+        let gaps_config = tiling_config.gaps_config();
+        ScrollingCompositor {
+            columns: Vec::new(),
+            offset: 0,
+            column_width: tiling_config.column_width(),
+            border_size,
+            inner_gap: gaps_config.as_ref().map_or(0, |gaps| gaps.inner_size()),
+            outer_gap: gaps_config.as_ref().map_or(0, |gaps| gaps.outer_size()),
+            smart_borders: tiling_config.smart_borders(),
+            smart_gaps: tiling_config.smart_gaps(),
+        }
+    }
+
+    /// Column and row of the window.
+    fn find(&self, window: u32) -> Option<(usize, usize)> {
+        self.columns
+            .iter()
+            .enumerate()
+            .find_map(|(column, windows)| {
+                let row = windows.windows.iter().position(|leaf| *leaf == window)?;
+                Some((column, row))
+            })
+    }
+
+    /// Drops the windows that are gone, new windows get a column right of the
+    /// focused one.
+    fn sync(&mut self, windows: &[u32], focused: Option<u32>) {
+        for column in &mut self.columns {
+            column.windows.retain(|window| windows.contains(window));
+        }
+        self.columns.retain(|column| !column.windows.is_empty());
+        for window in windows {
+            if self.find(*window).is_some() {
+                continue;
+            }
+            let index = focused
+                .and_then(|focused| self.find(focused))
+                .map_or(self.columns.len(), |(column, _)| column + 1);
+            self.columns.insert(
+                index,
+                Column {
+                    windows: vec![*window],
+                    width: self.column_width,
+                },
+            );
+        }
+    }
+
+    /// Left, right moves the window into the neighbouring column, or out of
+    /// its column when it shares it. Up, down moves it inside its column.
+    fn move_window(&mut self, window: u32, direction: Direction) {
+        let Some((column, row)) = self.find(window) else {
+            return;
+        };
+        match direction {
+            Direction::Up | Direction::Down => {
+                let windows = &mut self.columns[column].windows;
+                let other = match direction {
+                    Direction::Up => row.checked_sub(1),
+                    _ => Some(row + 1).filter(|other| *other < windows.len()),
+                };
+                if let Some(other) = other {
+                    windows.swap(row, other);
+                }
+            }
+            Direction::Left | Direction::Right => {
+                let right = direction == Direction::Right;
+                if self.columns[column].windows.len() > 1 {
+                    self.columns[column].windows.remove(row);
+                    let width = self.columns[column].width;
+                    let index = column + usize::from(right);
+                    self.columns.insert(
+                        index,
+                        Column {
+                            windows: vec![window],
+                            width,
+                        },
+                    );
+                    return;
+                }
+                let neighbour = match right {
+                    true => Some(column + 1).filter(|other| *other < self.columns.len()),
+                    false => column.checked_sub(1),
+                };
+                if let Some(neighbour) = neighbour {
+                    self.columns.remove(column);
+                    let neighbour = match right {
+                        true => neighbour - 1,
+                        false => neighbour,
+                    };
+                    self.columns[neighbour].windows.push(window);
+                }
+            }
+        }
+    }
+
+    /// Left edges and widths of the columns on the strip.
+    fn strip(&self, width: u16) -> Vec<(i32, i32)> {
+        let mut x = 0;
+        self.columns
+            .iter()
+            .map(|column| {
+                let column_width = (width as f32 * column.width).round().max(1.0) as i32;
+                let edges = (x, column_width);
+                x += column_width + self.inner_gap as i32;
+                edges
+            })
+            .collect()
+    }
+
+    /// Scrolls as little as possible to show the whole focused column.
+    fn scroll_to(&mut self, column: (i32, i32), width: u16) {
+        let (x, column_width) = column;
+        if x < self.offset || column_width > width as i32 {
+            self.offset = x;
+        } else if x + column_width > self.offset + width as i32 {
+            self.offset = x + column_width - width as i32;
+        }
+    }
+}
+
+impl Compositor for ScrollingCompositor {
+    fn locate_windows(&mut self, area: WindowGeometry, clients: &[Client]) -> Vec<Placement> {
+        let clients = tiled(clients);
+        let windows: Vec<u32> = clients
+            .iter()
+            .map(|client| client.window().resource_id())
+            .collect();
+        let focused = clients
+            .iter()
+            .find(|client| client.is_focused())
+            .map(|client| client.window().resource_id());
+        self.sync(&windows, focused);
+        let area = match clients.len() == 1 && self.smart_gaps {
+            true => area,
+            false => shrink(area, self.outer_gap),
+        };
+        let strip = self.strip(area.width());
+        let last = strip.last().map_or(0, |(x, width)| x + width);
+        self.offset = self.offset.min(last - area.width() as i32).max(0);
+        if let Some((column, _)) = focused.and_then(|focused| self.find(focused)) {
+            self.scroll_to(strip[column], area.width());
+        }
+        let mut cells: Vec<(u32, WindowGeometry)> = Vec::new();
+        let viewport = self.offset..=self.offset + area.width() as i32;
+        for (column, (x, width)) in self.columns.iter().zip(strip) {
+            let x = match viewport.contains(&x) && viewport.contains(&(x + width)) {
+                true => area.x() as i32 + x - self.offset,
+                // Next to the viewport it could be on another monitor.
+                false => -width,
+            }
+            .clamp(i16::MIN as i32, i16::MAX as i32);
+            let column_area = WindowGeometry::new(x as i16, area.y(), width as u16, area.height());
+            let rows = split(column_area, column.windows.len(), true, self.inner_gap);
+            cells.extend(column.windows.iter().copied().zip(rows));
+        }
+        let cells = windows
+            .iter()
+            .map(|window| {
+                cells
+                    .iter()
+                    .find(|(leaf, _)| leaf == window)
+                    .map_or(area, |(_, cell)| *cell)
+            })
+            .collect();
+        place(&clients, cells, self.border_size, self.smart_borders)
+    }
+
+    fn name(&self, _focused: Option<usize>, _count: usize) -> String {
+        "[S]".to_string()
+    }
+
+    fn handle_message(&mut self, message: LayoutMessage, focused: Option<x::Window>) {
+        let focused = focused.map(|focused| focused.resource_id());
+        match message {
+            LayoutMessage::Move(direction) => {
+                if let Some(focused) = focused {
+                    self.move_window(focused, direction);
+                }
+            }
+            LayoutMessage::Resize(direction, step) => {
+                let Some((column, _)) = focused.and_then(|focused| self.find(focused)) else {
+                    return;
+                };
+                let step = match direction {
+                    Direction::Right | Direction::Down => step,
+                    Direction::Left | Direction::Up => -step,
+                };
+                let width = &mut self.columns[column].width;
+                *width = (*width + step).clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH);
+            }
+            LayoutMessage::ChangeGaps(delta) => {
+                self.inner_gap = change_gap(self.inner_gap, delta);
+                self.outer_gap = change_gap(self.outer_gap, delta);
+            }
+            _ => {}
+        }
+    }
+
+    fn follows_focus(&self) -> bool {
+        true
+    }
+
+    fn save_state(&self) -> Option<String> {
+        toml::to_string(&ScrollingState {
+            columns: self.columns.clone(),
+            offset: self.offset,
+        })
+        .ok()
+    }
+
+    fn restore_state(&mut self, state: &str) {
+        if let Ok(state) = toml::from_str::<ScrollingState>(state) {
+            // A window is in one column once, empty columns are dropped.
+            let mut seen = Vec::new();
+            let mut columns = Vec::new();
+            for mut column in state.columns {
+                column.windows.retain(|window| match seen.contains(window) {
+                    true => false,
+                    false => {
+                        seen.push(*window);
+                        true
+                    }
+                });
+                if column.windows.is_empty() {
+                    continue;
+                }
+                column.width = match column.width.is_finite() {
+                    true => column.width.clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH),
+                    false => self.column_width,
+                };
+                columns.push(column);
+            }
+            self.columns = columns;
+            self.offset = state.offset;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        window::icccm::SizeHints,
+        window_manager::compositor::tests::{clients, window},
+    };
+
+    fn compositor(columns: &[&[u32]]) -> ScrollingCompositor {
+        let mut compositor = ScrollingCompositor::new(TilingConfig::default(), 0);
+        compositor.columns = columns
+            .iter()
+            .map(|windows| Column {
+                windows: windows.to_vec(),
+                width: 0.5,
+            })
+            .collect();
+        compositor
+    }
+
+    fn columns(compositor: &ScrollingCompositor) -> Vec<Vec<u32>> {
+        compositor
+            .columns
+            .iter()
+            .map(|column| column.windows.clone())
+            .collect()
+    }
+
+    #[test]
+    fn move_up_and_down_stays_in_the_column() {
+        let mut compositor = compositor(&[&[1], &[2, 3]]);
+        compositor.move_window(2, Direction::Down);
+        assert_eq!(columns(&compositor), vec![vec![1], vec![3, 2]]);
+        compositor.move_window(2, Direction::Down);
+        compositor.move_window(1, Direction::Up);
+        assert_eq!(columns(&compositor), vec![vec![1], vec![3, 2]]);
+    }
+
+    #[test]
+    fn move_sideways_leaves_or_joins_columns() {
+        let mut compositor = compositor(&[&[1], &[2, 3], &[4]]);
+        compositor.move_window(3, Direction::Right);
+        assert_eq!(
+            columns(&compositor),
+            vec![vec![1], vec![2], vec![3], vec![4]]
+        );
+        compositor.move_window(4, Direction::Left);
+        compositor.move_window(2, Direction::Left);
+        assert_eq!(columns(&compositor), vec![vec![1, 2], vec![3, 4]]);
+        compositor.move_window(1, Direction::Left);
+        assert_eq!(columns(&compositor), vec![vec![1], vec![2], vec![3, 4]]);
+        // Nothing on the left of the first column.
+        compositor.move_window(1, Direction::Left);
+        compositor.move_window(2, Direction::Left);
+        assert_eq!(columns(&compositor), vec![vec![1, 2], vec![3, 4]]);
+    }
+
+    #[test]
+    fn scroll_to_moves_as_little_as_possible() {
+        let mut compositor = compositor(&[]);
+        compositor.scroll_to((500, 400), 1000);
+        assert_eq!(compositor.offset, 0);
+        compositor.scroll_to((1200, 500), 1000);
+        assert_eq!(compositor.offset, 700);
+        compositor.scroll_to((500, 500), 1000);
+        assert_eq!(compositor.offset, 500);
+        // A column wider than the viewport shows its left part.
+        compositor.scroll_to((2000, 1200), 1000);
+        assert_eq!(compositor.offset, 2000);
+    }
+
+    #[test]
+    fn offset_stays_on_the_strip() {
+        let area = WindowGeometry::new(0, 0, 1000, 500);
+        let mut compositor = compositor(&[&[1], &[2], &[3]]);
+        compositor.offset = 2000;
+        compositor.locate_windows(area, &clients(3));
+        assert_eq!(compositor.offset, 500);
+        compositor.locate_windows(area, &clients(1));
+        assert_eq!(compositor.offset, 0);
+    }
+
+    #[test]
+    fn columns_out_of_view_leave_the_root_window() {
+        // The monitor on the right of a 1920 pixels wide one.
+        let area = WindowGeometry::new(1920, 0, 1000, 500);
+        let mut compositor = compositor(&[&[1], &[2], &[3]]);
+        let mut clients = clients(3);
+        clients[2] = Client::new(window(3), SizeHints::default(), false, true, None);
+        let placements = compositor.locate_windows(area, &clients);
+        assert_eq!(compositor.offset, 500);
+        let geometries: Vec<WindowGeometry> = placements
+            .iter()
+            .map(|placement| placement.geometry())
+            .collect();
+        assert_eq!(
+            geometries,
+            vec![
+                WindowGeometry::new(-500, 0, 500, 500),
+                WindowGeometry::new(1920, 0, 500, 500),
+                WindowGeometry::new(2420, 0, 500, 500),
+            ]
+        );
+    }
+
+    #[test]
+    fn columns_survive_a_restart() {
+        let mut compositor = compositor(&[&[1, 2], &[3]]);
+        compositor.offset = 100;
+        let state = compositor.save_state().unwrap();
+        let mut restored = ScrollingCompositor::new(TilingConfig::default(), 0);
+        restored.restore_state(&state);
+        assert_eq!(restored.columns, compositor.columns);
+        assert_eq!(restored.offset, 100);
+    }
+
+    #[test]
+    fn restored_columns_are_sanitized() {
+        let mut saved = compositor(&[&[1, 2, 1], &[2], &[3], &[4]]);
+        saved.columns[2].width = f32::NAN;
+        saved.columns[3].width = 5.0;
+        let mut restored = ScrollingCompositor::new(TilingConfig::default(), 0);
+        restored.restore_state(&saved.save_state().unwrap());
+        assert_eq!(
+            restored.columns,
+            vec![
+                Column {
+                    windows: vec![1, 2],
+                    width: 0.5,
+                },
+                Column {
+                    windows: vec![3],
+                    width: restored.column_width,
+                },
+                Column {
+                    windows: vec![4],
+                    width: MAX_COLUMN_WIDTH,
+                },
+            ]
+        );
+    }
+}
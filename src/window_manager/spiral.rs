@@ -0,0 +1,204 @@
+use xcb::x;
+
+use super::{
+    compositor::{Client, Compositor, LayoutMessage, Placement},
+    tiling::{change_gap, place, shrink, tiled},
+};
+use crate::{config::TilingConfig, window::WindowGeometry};
+
+/// Fibonacci layout: every window takes half of the space the previous one
+/// left. The halves turn around the center in spiral mode, and go down to
+/// the bottom right corner in dwindle mode.
+pub struct SpiralCompositor {
+    dwindle: bool,
+    border_size: u16,
+    inner_gap: u16,
+    outer_gap: u16,
+    smart_borders: bool,
+    smart_gaps: bool,
+}
+
+impl SpiralCompositor {
+    pub fn new(tiling_config: TilingConfig, border_size: u16, dwindle: bool) -> SpiralCompositor {
+        let gaps_config = tiling_config.gaps_config();
+        SpiralCompositor {
+            dwindle,
+            border_size,
+            inner_gap: gaps_config.as_ref().map_or(0, |gaps| gaps.inner_size()),
+            outer_gap: gaps_config.as_ref().map_or(0, |gaps| gaps.outer_size()),
+            smart_borders: tiling_config.smart_borders(),
+            smart_gaps: tiling_config.smart_gaps(),
+        }
+    }
+}
+
+/// Cells of `count` windows. The area is split side by side first, then one
+/// above the other, and so on. In spiral mode every second split gives the
+/// window the right or bottom half instead of the left or top one.
+pub fn fibonacci(
+    area: WindowGeometry,
+    count: usize,
+    gap: u16,
+    dwindle: bool,
+) -> Vec<WindowGeometry> {
+    let mut cells = Vec::new();
+    let mut rest = area;
+    for index in 0..count {
+        if index + 1 == count {
+            cells.push(rest);
+            break;
+        }
+        let vertical = index % 2 == 1;
+        let length = match vertical {
+            true => rest.height(),
+            false => rest.width(),
+        };
+        let first = length.saturating_sub(gap) / 2;
+        let second = length.saturating_sub(gap).saturating_sub(first);
+        if first == 0 || second == 0 {
+            // No room for another split, the remaining windows share the cell.
+            cells.extend(std::iter::repeat_n(rest, count - index));
+            break;
+        }
+        let (start, end) = match vertical {
+            true => (
+                WindowGeometry::new(rest.x(), rest.y(), rest.width(), first),
+                WindowGeometry::new(
+                    rest.x(),
+                    rest.y() + (first + gap) as i16,
+                    rest.width(),
+                    second,
+                ),
+            ),
+            false => (
+                WindowGeometry::new(rest.x(), rest.y(), first, rest.height()),
+                WindowGeometry::new(
+                    rest.x() + (first + gap) as i16,
+                    rest.y(),
+                    second,
+                    rest.height(),
+                ),
+            ),
+        };
+        let (cell, next) = match !dwindle && index % 4 >= 2 {
+            true => (end, start),
+            false => (start, end),
+        };
+        cells.push(cell);
+        rest = next;
+    }
+    cells
+}
+
+impl Compositor for SpiralCompositor {
+    fn locate_windows(&mut self, area: WindowGeometry, clients: &[Client]) -> Vec<Placement> {
+        let clients = tiled(clients);
+        let area = match clients.len() == 1 && self.smart_gaps {
+            true => area,
+            false => shrink(area, self.outer_gap),
+        };
+        let cells = fibonacci(area, clients.len(), self.inner_gap, self.dwindle);
+        place(&clients, cells, self.border_size, self.smart_borders)
+    }
+
+    fn name(&self, _focused: Option<usize>, _count: usize) -> String {
+        match self.dwindle {
+            true => "[\\]".to_string(),
+            false => "[@]".to_string(),
+        }
+    }
+
+    fn handle_message(&mut self, message: LayoutMessage, _focused: Option<x::Window>) {
+        if let LayoutMessage::ChangeGaps(delta) = message {
+            self.inner_gap = change_gap(self.inner_gap, delta);
+            self.outer_gap = change_gap(self.outer_gap, delta);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_manager::compositor::tests::cells;
+
+    fn area() -> WindowGeometry {
+        WindowGeometry::new(0, 0, 1200, 600)
+    }
+
+    #[test]
+    fn every_window_takes_half_of_the_rest() {
+        for dwindle in [false, true] {
+            assert_eq!(fibonacci(area(), 0, 0, dwindle), Vec::new());
+            assert_eq!(fibonacci(area(), 1, 0, dwindle), vec![area()]);
+            assert_eq!(
+                fibonacci(area(), 2, 0, dwindle),
+                cells(&[(0, 0, 600, 600), (600, 0, 600, 600)])
+            );
+            assert_eq!(
+                fibonacci(area(), 3, 0, dwindle),
+                cells(&[(0, 0, 600, 600), (600, 0, 600, 300), (600, 300, 600, 300)])
+            );
+        }
+    }
+
+    #[test]
+    fn spiral_turns_around_the_center() {
+        assert_eq!(
+            fibonacci(area(), 4, 0, false),
+            cells(&[
+                (0, 0, 600, 600),
+                (600, 0, 600, 300),
+                (900, 300, 300, 300),
+                (600, 300, 300, 300),
+            ])
+        );
+        assert_eq!(
+            fibonacci(area(), 5, 0, false),
+            cells(&[
+                (0, 0, 600, 600),
+                (600, 0, 600, 300),
+                (900, 300, 300, 300),
+                (600, 450, 300, 150),
+                (600, 300, 300, 150),
+            ])
+        );
+    }
+
+    #[test]
+    fn dwindle_goes_to_the_bottom_right() {
+        assert_eq!(
+            fibonacci(area(), 4, 0, true),
+            cells(&[
+                (0, 0, 600, 600),
+                (600, 0, 600, 300),
+                (600, 300, 300, 300),
+                (900, 300, 300, 300),
+            ])
+        );
+        assert_eq!(
+            fibonacci(area(), 5, 0, true),
+            cells(&[
+                (0, 0, 600, 600),
+                (600, 0, 600, 300),
+                (600, 300, 300, 300),
+                (900, 300, 300, 150),
+                (900, 450, 300, 150),
+            ])
+        );
+    }
+
+    #[test]
+    fn gaps_separate_the_halves() {
+        let area = WindowGeometry::new(0, 0, 1210, 610);
+        assert_eq!(
+            fibonacci(area, 3, 10, false),
+            cells(&[(0, 0, 600, 610), (610, 0, 600, 300), (610, 310, 600, 300)])
+        );
+    }
+
+    #[test]
+    fn windows_share_a_cell_too_small_to_split() {
+        let area = WindowGeometry::new(0, 0, 1, 600);
+        assert_eq!(fibonacci(area, 2, 0, false), vec![area, area]);
+    }
+}
@@ -0,0 +1,62 @@
+use xcb::x;
+
+use super::WindowManager;
+use crate::window::{ewmh::NetWmState, XWindow};
+
+/// Stacking layer of a window, higher layers are always above lower ones.
+/// Floating windows stay above the tiled ones and their titles.
+fn layer(window: &XWindow) -> u8 {
+    if window.has_state(NetWmState::Fullscreen) {
+        4
+    } else if window.has_state(NetWmState::Above) {
+        3
+    } else if window.has_state(NetWmState::Below) {
+        0
+    } else if window.is_floating() {
+        2
+    } else {
+        1
+    }
+}
+
+impl WindowManager {
+    /// Puts the window on top of the other windows of its layer.
+    pub(super) fn raise(&mut self, window: x::Window) {
+        if !self.is_managed(window) {
+            return;
+        }
+        self.stacking.retain(|managed| *managed != window);
+        self.stacking.push(window);
+        self.restack();
+    }
+
+    /// Sorts the windows by layer and applies the order to the X server.
+    pub(super) fn restack(&mut self) {
+        let mut stacking = std::mem::take(&mut self.stacking);
+        stacking.sort_by_key(|window| self.find_window(*window).map(layer));
+        // Titles go above the tiled windows, below the floating ones.
+        let tiled = stacking
+            .iter()
+            .take_while(|window| self.find_window(**window).map(layer) <= Some(1))
+            .count();
+        let mut order = stacking.clone();
+        order.splice(tiled..tiled, self.title_bars.windows());
+        for pair in order.windows(2) {
+            self.x_server_connecton.send_request(&x::ConfigureWindow {
+                window: pair[1],
+                value_list: &[
+                    x::ConfigWindow::Sibling(pair[0]),
+                    x::ConfigWindow::StackMode(x::StackMode::Above),
+                ],
+            });
+        }
+        self.stacking = stacking;
+        self.ewmh.set_client_list_stacking(&self.stacking);
+    }
+
+    pub(super) fn update_client_list(&self) {
+        let windows: Vec<x::Window> = self.windows.iter().map(|window| window.window()).collect();
+        self.ewmh.set_client_list(&windows);
+        self.ewmh.set_client_list_stacking(&self.stacking);
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use super::{
+    bsp::BspCompositor, centered_master::CenteredMasterCompositor, compositor::Compositor,
+    containers::ContainerCompositor, floating::FloatingCompositor, grid::GridCompositor,
+    monocle::MonocleCompositor, scrolling::ScrollingCompositor, spiral::SpiralCompositor,
+    tiling::TilingCompositor,
+};
+use crate::config::{Config, LayoutKind};
+
+/// Layout of a tag. Every layout the tag used keeps its parameters, so they
+/// are back when the tag switches to it again.
+pub struct TagLayout {
+    kind: LayoutKind,
+    compositors: HashMap<LayoutKind, Box<dyn Compositor>>,
+}
+
+impl TagLayout {
+    pub fn new(kind: LayoutKind, config: &Config) -> TagLayout {
+        let mut compositors = HashMap::new();
+        compositors.insert(kind, new_compositor(kind, config));
+        TagLayout { kind, compositors }
+    }
+
+    pub fn kind(&self) -> LayoutKind {
+        self.kind
+    }
+
+    pub fn set_kind(&mut self, kind: LayoutKind, config: &Config) {
+        self.compositors
+            .entry(kind)
+            .or_insert_with(|| new_compositor(kind, config));
+        self.kind = kind;
+    }
+
+    pub fn compositor(&self) -> &dyn Compositor {
+        self.compositors[&self.kind].as_ref()
+    }
+
+    pub fn compositor_mut(&mut self) -> &mut dyn Compositor {
+        self.compositors.get_mut(&self.kind).unwrap().as_mut()
+    }
+}
+
+/// Layouts of every tag, in tag order, as the config sets them.
+pub fn tag_layouts(config: &Config) -> Vec<TagLayout> {
+    config
+        .tags()
+        .iter()
+        .map(|tag| TagLayout::new(config.tag_layout(tag), config))
+        .collect()
+}
+
+fn new_compositor(layout: LayoutKind, config: &Config) -> Box<dyn Compositor> {
+    let border_size = config.window_config().border_size();
+    match layout {
+        LayoutKind::Tile => Box::new(TilingCompositor::new(config.tiling_config(), border_size)),
+        LayoutKind::Monocle => {
+            Box::new(MonocleCompositor::new(config.tiling_config(), border_size))
+        }
+        LayoutKind::Grid => Box::new(GridCompositor::new(config.tiling_config(), border_size)),
+        LayoutKind::Spiral => Box::new(SpiralCompositor::new(
+            config.tiling_config(),
+            border_size,
+            false,
+        )),
+        LayoutKind::Dwindle => Box::new(SpiralCompositor::new(
+            config.tiling_config(),
+            border_size,
+            true,
+        )),
+        LayoutKind::CenteredMaster => Box::new(CenteredMasterCompositor::new(
+            config.tiling_config(),
+            border_size,
+        )),
+        LayoutKind::Bsp => Box::new(BspCompositor::new(config.tiling_config(), border_size)),
+        LayoutKind::Containers => Box::new(ContainerCompositor::new(
+            config.tiling_config(),
+            border_size,
+        )),
+        LayoutKind::Scrolling => Box::new(ScrollingCompositor::new(
+            config.tiling_config(),
+            border_size,
+        )),
+        LayoutKind::Floating => Box::new(FloatingCompositor::new(border_size)),
+    }
+}
@@ -1,24 +1,431 @@
-use super::Compositor;
-use crate::config::TilingConfig;
-use std::sync::Arc;
-use xcb::Connection;
+use xcb::x;
 
+use super::compositor::{Client, Compositor, LayoutMessage, Placement};
+use crate::{
+    config::{MasterPosition, TilingConfig, MAX_GAP},
+    window::{icccm::SizeHints, WindowGeometry},
+};
+
+pub const MIN_MASTER_RATIO: f32 = 0.05;
+pub const MAX_MASTER_RATIO: f32 = 0.95;
+
+/// dwm style layout: the master windows share one part of the area, the
+/// other windows are stacked in the rest.
 pub struct TilingCompositor {
-    tiling_config: TilingConfig,
-    x_connection: Arc<Connection>,
+    master_stack: MasterStack,
+    border_size: u16,
+    smart_borders: bool,
+}
+
+/// Parameters of the master/stack layout.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MasterStack {
+    master_count: usize,
+    master_ratio: f32,
+    master_position: MasterPosition,
+    inner_gap: u16,
+    outer_gap: u16,
+    smart_gaps: bool,
+}
+
+impl MasterStack {
+    pub fn new(tiling_config: &TilingConfig) -> MasterStack {
+        let gaps_config = tiling_config.gaps_config();
+        MasterStack {
+            master_count: tiling_config.master_count(),
+            master_ratio: tiling_config
+                .master_ratio()
+                .clamp(MIN_MASTER_RATIO, MAX_MASTER_RATIO),
+            master_position: tiling_config.master_position(),
+            inner_gap: gaps_config.as_ref().map_or(0, |gaps| gaps.inner_size()),
+            outer_gap: gaps_config.as_ref().map_or(0, |gaps| gaps.outer_size()),
+            smart_gaps: tiling_config.smart_gaps(),
+        }
+    }
+
+    pub fn change_master_count(&mut self, delta: i32) {
+        self.set_master_count(self.master_count.saturating_add_signed(delta as isize));
+    }
+
+    pub fn set_master_count(&mut self, master_count: usize) {
+        self.master_count = master_count;
+    }
+
+    pub fn change_master_ratio(&mut self, delta: f32) {
+        self.set_master_ratio(self.master_ratio + delta);
+    }
+
+    pub fn set_master_ratio(&mut self, master_ratio: f32) {
+        self.master_ratio = master_ratio.clamp(MIN_MASTER_RATIO, MAX_MASTER_RATIO);
+    }
+
+    pub fn set_master_position(&mut self, master_position: MasterPosition) {
+        self.master_position = master_position;
+    }
+
+    pub fn change_gaps(&mut self, delta: i32) {
+        self.inner_gap = change_gap(self.inner_gap, delta);
+        self.outer_gap = change_gap(self.outer_gap, delta);
+    }
+
+    /// Cells of `count` windows in the area, masters first. The cells include
+    /// the window borders.
+    pub fn layout(&self, area: WindowGeometry, count: usize) -> Vec<WindowGeometry> {
+        if count == 0 {
+            return Vec::new();
+        }
+        if count == 1 && self.smart_gaps {
+            return vec![area];
+        }
+        let area = shrink(area, self.outer_gap);
+        let masters = self.master_count.min(count);
+        let stacked = count - masters;
+        let horizontal = matches!(
+            self.master_position,
+            MasterPosition::Left | MasterPosition::Right
+        );
+        if masters == 0 || stacked == 0 {
+            return split(area, count, horizontal, self.inner_gap);
+        }
+
+        let length = match horizontal {
+            true => area.width(),
+            false => area.height(),
+        }
+        .saturating_sub(self.inner_gap);
+        let master_length = (length as f32 * self.master_ratio).round() as u16;
+        let stack_length = length - master_length;
+        let (first_length, second_length) = match self.master_position {
+            MasterPosition::Left | MasterPosition::Top => (master_length, stack_length),
+            MasterPosition::Right | MasterPosition::Bottom => (stack_length, master_length),
+        };
+        let (first, second) = match horizontal {
+            true => (
+                WindowGeometry::new(area.x(), area.y(), first_length, area.height()),
+                WindowGeometry::new(
+                    area.x()
+                        .saturating_add_unsigned(first_length.saturating_add(self.inner_gap)),
+                    area.y(),
+                    second_length,
+                    area.height(),
+                ),
+            ),
+            false => (
+                WindowGeometry::new(area.x(), area.y(), area.width(), first_length),
+                WindowGeometry::new(
+                    area.x(),
+                    area.y()
+                        .saturating_add_unsigned(first_length.saturating_add(self.inner_gap)),
+                    area.width(),
+                    second_length,
+                ),
+            ),
+        };
+        let (master_area, stack_area) = match self.master_position {
+            MasterPosition::Left | MasterPosition::Top => (first, second),
+            MasterPosition::Right | MasterPosition::Bottom => (second, first),
+        };
+        let mut cells = split(master_area, masters, horizontal, self.inner_gap);
+        cells.extend(split(stack_area, stacked, horizontal, self.inner_gap));
+        cells
+    }
+}
+
+/// Clients that aren't floating, in layout order.
+pub fn tiled(clients: &[Client]) -> Vec<&Client> {
+    clients
+        .iter()
+        .filter(|client| !client.is_floating())
+        .collect()
+}
+
+/// Fits the clients in their cells. A single client has no border with
+/// `smart_borders`.
+pub fn place(
+    clients: &[&Client],
+    cells: Vec<WindowGeometry>,
+    border_size: u16,
+    smart_borders: bool,
+) -> Vec<Placement> {
+    let border_size = match clients.len() == 1 && smart_borders {
+        true => 0,
+        false => border_size,
+    };
+    clients
+        .iter()
+        .zip(cells)
+        .map(|(client, cell)| {
+            let geometry = fit(cell, border_size, &client.size_hints());
+            Placement::new(client.window(), geometry, border_size)
+        })
+        .collect()
+}
+
+pub fn change_gap(gap: u16, delta: i32) -> u16 {
+    (gap as i32).saturating_add(delta).clamp(0, MAX_GAP as i32) as u16
+}
+
+pub fn shrink(area: WindowGeometry, gap: u16) -> WindowGeometry {
+    WindowGeometry::new(
+        area.x().saturating_add_unsigned(gap),
+        area.y().saturating_add_unsigned(gap),
+        area.width().saturating_sub(gap.saturating_mul(2)).max(1),
+        area.height().saturating_sub(gap.saturating_mul(2)).max(1),
+    )
+}
+
+/// Splits the area into `count` cells of the same size, one above the other if
+/// `vertical`. The last cell takes what's left after rounding.
+pub fn split(area: WindowGeometry, count: usize, vertical: bool, gap: u16) -> Vec<WindowGeometry> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let total = match vertical {
+        true => area.height(),
+        false => area.width(),
+    };
+    let gaps = gap as u32 * (count as u32 - 1);
+    let length = (total as u32).saturating_sub(gaps) / count as u32;
+    (0..count as u32)
+        .map(|index| {
+            let offset = (length + gap as u32) * index;
+            let cell_length = match index + 1 == count as u32 {
+                true => (total as u32).saturating_sub(offset),
+                false => length,
+            }
+            .max(1) as u16;
+            match vertical {
+                true => WindowGeometry::new(
+                    area.x(),
+                    area.y().saturating_add_unsigned(offset as u16),
+                    area.width(),
+                    cell_length,
+                ),
+                false => WindowGeometry::new(
+                    area.x().saturating_add_unsigned(offset as u16),
+                    area.y(),
+                    cell_length,
+                    area.height(),
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Geometry of a window with the border inside the cell, at a size its size
+/// hints allow. Increments and the aspect ratio round the size down, a min
+/// size larger than the cell grows out of it.
+pub fn fit(cell: WindowGeometry, border_size: u16, size_hints: &SizeHints) -> WindowGeometry {
+    let width = cell
+        .width()
+        .saturating_sub(border_size.saturating_mul(2))
+        .max(1) as u32;
+    let height = cell
+        .height()
+        .saturating_sub(border_size.saturating_mul(2))
+        .max(1) as u32;
+    let (width, height) = size_hints.constrain(width, height);
+    WindowGeometry::new(
+        cell.x(),
+        cell.y(),
+        width.min(u16::MAX as u32) as u16,
+        height.min(u16::MAX as u32) as u16,
+    )
 }
 
 impl TilingCompositor {
-    pub fn new(tiling_config: TilingConfig, x_connection: Arc<Connection>) -> TilingCompositor {
+    pub fn new(tiling_config: TilingConfig, border_size: u16) -> TilingCompositor {
         TilingCompositor {
-            tiling_config,
-            x_connection,
+            master_stack: MasterStack::new(&tiling_config),
+            border_size,
+            smart_borders: tiling_config.smart_borders(),
         }
     }
 }
 
 impl Compositor for TilingCompositor {
-    fn locate_windows(&self) {
-        todo!()
+    fn locate_windows(&mut self, area: WindowGeometry, clients: &[Client]) -> Vec<Placement> {
+        let clients = tiled(clients);
+        let cells = self.master_stack.layout(area, clients.len());
+        place(&clients, cells, self.border_size, self.smart_borders)
+    }
+
+    fn name(&self, _focused: Option<usize>, _count: usize) -> String {
+        "[]=".to_string()
+    }
+
+    fn handle_message(&mut self, message: LayoutMessage, _focused: Option<x::Window>) {
+        match message {
+            LayoutMessage::ChangeMasterCount(delta) => self.master_stack.change_master_count(delta),
+            LayoutMessage::SetMasterCount(count) => self.master_stack.set_master_count(count),
+            LayoutMessage::ChangeMasterRatio(delta) => self.master_stack.change_master_ratio(delta),
+            LayoutMessage::SetMasterRatio(ratio) => self.master_stack.set_master_ratio(ratio),
+            LayoutMessage::SetMasterPosition(position) => {
+                self.master_stack.set_master_position(position)
+            }
+            LayoutMessage::ChangeGaps(delta) => self.master_stack.change_gaps(delta),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_manager::compositor::tests::cells;
+
+    fn master_stack(
+        master_position: MasterPosition,
+        master_count: usize,
+        master_ratio: f32,
+        inner_gap: u16,
+        outer_gap: u16,
+    ) -> MasterStack {
+        MasterStack {
+            master_count,
+            master_ratio,
+            master_position,
+            inner_gap,
+            outer_gap,
+            smart_gaps: false,
+        }
+    }
+
+    fn area() -> WindowGeometry {
+        WindowGeometry::new(0, 0, 1000, 500)
+    }
+
+    #[test]
+    fn masters_take_the_configured_side() {
+        let layout = |position| master_stack(position, 1, 0.6, 0, 0).layout(area(), 3);
+        assert_eq!(
+            layout(MasterPosition::Left),
+            cells(&[(0, 0, 600, 500), (600, 0, 400, 250), (600, 250, 400, 250)])
+        );
+        assert_eq!(
+            layout(MasterPosition::Right),
+            cells(&[(400, 0, 600, 500), (0, 0, 400, 250), (0, 250, 400, 250)])
+        );
+        assert_eq!(
+            layout(MasterPosition::Top),
+            cells(&[(0, 0, 1000, 300), (0, 300, 500, 200), (500, 300, 500, 200)])
+        );
+        assert_eq!(
+            layout(MasterPosition::Bottom),
+            cells(&[(0, 200, 1000, 300), (0, 0, 500, 200), (500, 0, 500, 200)])
+        );
+    }
+
+    #[test]
+    fn windows_share_the_area_without_a_stack_or_masters() {
+        let no_masters = master_stack(MasterPosition::Left, 0, 0.6, 0, 0);
+        assert_eq!(
+            no_masters.layout(area(), 3),
+            cells(&[(0, 0, 1000, 166), (0, 166, 1000, 166), (0, 332, 1000, 168)])
+        );
+        let all_masters = master_stack(MasterPosition::Left, 5, 0.6, 0, 0);
+        assert_eq!(
+            all_masters.layout(area(), 2),
+            cells(&[(0, 0, 1000, 250), (0, 250, 1000, 250)])
+        );
+        assert_eq!(all_masters.layout(area(), 0), Vec::new());
+    }
+
+    #[test]
+    fn gaps_surround_the_cells() {
+        let master_stack = master_stack(MasterPosition::Left, 1, 0.5, 10, 20);
+        assert_eq!(
+            master_stack.layout(area(), 3),
+            cells(&[
+                (20, 20, 475, 460),
+                (505, 20, 475, 225),
+                (505, 255, 475, 225)
+            ])
+        );
+        assert_eq!(master_stack.layout(area(), 1), cells(&[(20, 20, 960, 460)]));
+        let smart_gaps = MasterStack {
+            smart_gaps: true,
+            ..master_stack
+        };
+        assert_eq!(smart_gaps.layout(area(), 1), vec![area()]);
+        assert_eq!(smart_gaps.layout(area(), 3), master_stack.layout(area(), 3));
+    }
+
+    #[test]
+    fn parameters_are_clamped() {
+        let mut master_stack = master_stack(MasterPosition::Left, 1, 0.5, 5, 5);
+        master_stack.set_master_ratio(2.0);
+        assert_eq!(master_stack.master_ratio, MAX_MASTER_RATIO);
+        master_stack.change_master_ratio(-1.0);
+        assert_eq!(master_stack.master_ratio, MIN_MASTER_RATIO);
+        master_stack.change_master_count(-3);
+        assert_eq!(master_stack.master_count, 0);
+        master_stack.change_gaps(-10);
+        assert_eq!((master_stack.inner_gap, master_stack.outer_gap), (0, 0));
+        master_stack.change_gaps(i32::MAX);
+        master_stack.change_gaps(i32::MAX);
+        assert_eq!(
+            (master_stack.inner_gap, master_stack.outer_gap),
+            (MAX_GAP, MAX_GAP)
+        );
+        master_stack.change_gaps(i32::MIN);
+        assert_eq!((master_stack.inner_gap, master_stack.outer_gap), (0, 0));
+        master_stack.change_master_count(i32::MIN);
+        assert_eq!(master_stack.master_count, 0);
+        master_stack.set_master_count(usize::MAX);
+        master_stack.change_master_count(i32::MAX);
+        assert_eq!(master_stack.master_count, usize::MAX);
+    }
+
+    #[test]
+    fn large_gaps_and_borders_leave_a_pixel() {
+        let area = WindowGeometry::new(i16::MAX - 10, 0, 100, 100);
+        assert_eq!(
+            shrink(area, u16::MAX),
+            WindowGeometry::new(i16::MAX, i16::MAX, 1, 1)
+        );
+        let master_stack = master_stack(MasterPosition::Top, 1, 0.5, MAX_GAP, MAX_GAP);
+        assert_eq!(master_stack.layout(area, 3).len(), 3);
+        assert_eq!(
+            fit(area, u16::MAX, &SizeHints::default()),
+            WindowGeometry::new(i16::MAX - 10, 0, 1, 1)
+        );
+    }
+
+    #[test]
+    fn split_gives_the_rest_to_the_last_cell() {
+        assert_eq!(
+            split(WindowGeometry::new(10, 10, 100, 50), 3, false, 5),
+            cells(&[(10, 10, 30, 50), (45, 10, 30, 50), (80, 10, 30, 50)])
+        );
+        assert_eq!(
+            split(WindowGeometry::new(0, 0, 100, 51), 2, true, 0),
+            cells(&[(0, 0, 100, 25), (0, 25, 100, 26)])
+        );
+        assert_eq!(split(area(), 0, true, 0), Vec::new());
+    }
+
+    #[test]
+    fn fit_keeps_the_size_hints() {
+        // PMinSize | PMaxSize
+        let size_hints = |min: (u32, u32), max: (u32, u32)| {
+            let mut value = [0; 18];
+            value[0] = 1 << 4 | 1 << 5;
+            [value[5], value[6], value[7], value[8]] = [min.0, min.1, max.0, max.1];
+            SizeHints::parse(&value)
+        };
+        let cell = WindowGeometry::new(10, 20, 100, 100);
+        assert_eq!(
+            fit(cell, 2, &SizeHints::default()),
+            WindowGeometry::new(10, 20, 96, 96)
+        );
+        assert_eq!(
+            fit(cell, 2, &size_hints((200, 150), (0, 0))),
+            WindowGeometry::new(10, 20, 200, 150)
+        );
+        assert_eq!(
+            fit(cell, 2, &size_hints((0, 0), (50, 40))),
+            WindowGeometry::new(10, 20, 50, 40)
+        );
     }
 }
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use xcb::{x, Connection};
+
+use super::compositor::TitleBar;
+
+const FOCUSED_BACKGROUND: u32 = 0x285577;
+const FOCUSED_TEXT: u32 = 0xffffff;
+const BACKGROUND: u32 = 0x222222;
+const TEXT: u32 = 0x888888;
+const BASELINE: i16 = 13;
+const PADDING: i16 = 4;
+
+/// Windows showing the titles of tabbed and stacked windows, drawn with the
+/// core `fixed` font.
+pub struct TitleBars {
+    connection: Arc<Connection>,
+    root: x::Window,
+    font: x::Font,
+    gc: x::Gcontext,
+    bars: Vec<(x::Window, TitleBar)>,
+}
+
+impl TitleBars {
+    pub fn new(connection: Arc<Connection>, root: x::Window) -> TitleBars {
+        let font = connection.generate_id();
+        connection.send_request(&x::OpenFont {
+            fid: font,
+            name: b"fixed",
+        });
+        let gc = connection.generate_id();
+        connection.send_request(&x::CreateGc {
+            cid: gc,
+            drawable: x::Drawable::Window(root),
+            value_list: &[x::Gc::Font(font)],
+        });
+        TitleBars {
+            connection,
+            root,
+            font,
+            gc,
+            bars: Vec::new(),
+        }
+    }
+
+    /// Creates, moves and destroys the windows so there is one per title.
+    pub fn update(&mut self, titles: Vec<TitleBar>) {
+        while self.bars.len() > titles.len() {
+            let (window, _) = self.bars.pop().unwrap();
+            self.connection.send_request(&x::DestroyWindow { window });
+        }
+        while self.bars.len() < titles.len() {
+            let window = self.create_window();
+            self.bars.push((window, titles[self.bars.len()]));
+        }
+        for ((window, bar), title) in self.bars.iter_mut().zip(titles) {
+            *bar = title;
+            let geometry = title.geometry();
+            self.connection.send_request(&x::ConfigureWindow {
+                window: *window,
+                value_list: &[
+                    x::ConfigWindow::X(geometry.x() as i32),
+                    x::ConfigWindow::Y(geometry.y() as i32),
+                    x::ConfigWindow::Width(geometry.width() as u32),
+                    x::ConfigWindow::Height(geometry.height() as u32),
+                ],
+            });
+        }
+    }
+
+    fn create_window(&self) -> x::Window {
+        let window = self.connection.generate_id();
+        self.connection.send_request(&x::CreateWindow {
+            depth: x::COPY_FROM_PARENT as u8,
+            wid: window,
+            parent: self.root,
+            x: 0,
+            y: 0,
+            width: 1,
+            height: 1,
+            border_width: 0,
+            class: x::WindowClass::InputOutput,
+            visual: x::COPY_FROM_PARENT,
+            value_list: &[
+                x::Cw::BackPixel(BACKGROUND),
+                x::Cw::OverrideRedirect(true),
+                x::Cw::EventMask(x::EventMask::EXPOSURE | x::EventMask::BUTTON_PRESS),
+            ],
+        });
+        self.connection.send_request(&x::MapWindow { window });
+        window
+    }
+
+    /// Draws every title, `name` gives the text of a client window.
+    pub fn draw(&self, focused: Option<x::Window>, name: impl Fn(x::Window) -> String) {
+        for (window, bar) in &self.bars {
+            let (background, text) = match Some(bar.window()) == focused {
+                true => (FOCUSED_BACKGROUND, FOCUSED_TEXT),
+                false => (BACKGROUND, TEXT),
+            };
+            let geometry = bar.geometry();
+            self.connection.send_request(&x::ChangeGc {
+                gc: self.gc,
+                value_list: &[x::Gc::Foreground(background)],
+            });
+            self.connection.send_request(&x::PolyFillRectangle {
+                drawable: x::Drawable::Window(*window),
+                gc: self.gc,
+                rectangles: &[x::Rectangle {
+                    x: 0,
+                    y: 0,
+                    width: geometry.width(),
+                    height: geometry.height(),
+                }],
+            });
+            self.connection.send_request(&x::ChangeGc {
+                gc: self.gc,
+                value_list: &[x::Gc::Foreground(text), x::Gc::Background(background)],
+            });
+            // Only ASCII is drawn, and a request takes at most 255 characters.
+            let name: Vec<u8> = name(bar.window())
+                .chars()
+                .map(|char| match char.is_ascii() {
+                    true => char as u8,
+                    false => b'?',
+                })
+                .take(255)
+                .collect();
+            self.connection.send_request(&x::ImageText8 {
+                drawable: x::Drawable::Window(*window),
+                gc: self.gc,
+                x: PADDING,
+                y: BASELINE,
+                string: &name,
+            });
+        }
+    }
+
+    /// Client window of the title shown by the bar window.
+    pub fn client(&self, bar_window: x::Window) -> Option<x::Window> {
+        self.bars
+            .iter()
+            .find(|(window, _)| *window == bar_window)
+            .map(|(_, bar)| bar.window())
+    }
+
+    pub fn windows(&self) -> Vec<x::Window> {
+        self.bars.iter().map(|(window, _)| *window).collect()
+    }
+}
+
+impl Drop for TitleBars {
+    fn drop(&mut self) {
+        for (window, _) in &self.bars {
+            self.connection
+                .send_request(&x::DestroyWindow { window: *window });
+        }
+        self.connection.send_request(&x::FreeGc { gc: self.gc });
+        self.connection
+            .send_request(&x::CloseFont { font: self.font });
+    }
+}